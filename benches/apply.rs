@@ -0,0 +1,55 @@
+//! `State::apply`などの速さと、呼び出しごとにメモリ確保をしていないことを確かめる。
+//! `cargo bench --bench apply`で実行する。
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use cube::moves::MOVES;
+use cube::State;
+
+/// メモリ確保の回数を数えるアロケータ
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ITERATIONS: usize = 10_000_000;
+
+fn bench(name: &str, f: impl Fn(State) -> State) {
+    let mut st = State::new_solved();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        st = f(black_box(st));
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    black_box(st);
+
+    println!("{:<12} {:>8.2} ns/回  メモリ確保 {} 回", name, elapsed.as_nanos() as f64 / ITERATIONS as f64, allocations);
+}
+
+fn main() {
+    let r = State::get_move(MOVES::R);
+
+    bench("apply", |st| st.apply(&r));
+    bench("apply_move", |st| st.apply_move(MOVES::U.into()));
+    bench("get_prime", |st| st.get_prime());
+}
@@ -0,0 +1,362 @@
+//! 展開済みの動きの列として手順を扱う。
+//!
+//! 同じ軸の動き(e.g. R, L, M, Rw, x)は互いに交換できるので、並んでいる同じ軸の動きは
+//! 各層を回す量だけで決まる。simplifyはこれを使って手順を短くする。
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::moves::{self, Axis, Layer, Move, Rotation};
+use crate::notation::{self, ParseError, Sequence};
+use crate::State;
+
+/// 手数の数え方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Metric {
+    /// 面を回す動きと2層回しを1手、スライスを2手と数える。持ち替えは数えない。
+    #[default]
+    Htm,
+    /// 90度を1手、180度を2手と数える。スライスはその2倍。持ち替えは数えない。
+    Qtm,
+    /// 面とスライスと2層回しを1手と数える。持ち替えは数えない。
+    Stm,
+    /// 持ち替えも含めてすべての動きを1手と数える。
+    Etm,
+}
+
+impl Metric {
+    /// 1つの動きの手数
+    pub fn cost(self, mv: Move) -> usize {
+        let slice = matches!(mv.layer(), Layer::M | Layer::E | Layer::S);
+        let rotation = matches!(mv.layer(), Layer::X | Layer::Y | Layer::Z);
+        let quarters = if mv.amount() == 2 { 2 } else { 1 };
+
+        match self {
+            Metric::Htm if rotation => 0,
+            Metric::Htm if slice => 2,
+            Metric::Htm => 1,
+            Metric::Qtm if rotation => 0,
+            Metric::Qtm if slice => quarters * 2,
+            Metric::Qtm => quarters,
+            Metric::Stm if rotation => 0,
+            Metric::Stm | Metric::Etm => 1,
+        }
+    }
+}
+
+/// simplifyの設定
+#[derive(Debug, Clone, Default)]
+pub struct SimplifyOptions {
+    /// 手数の数え方。同じ軸の動きはこの数え方で最も短くなるように書き直す。
+    pub metric: Metric,
+    /// 2層回しとスライスを他の動きとまとめて書き直してよいか。
+    /// falseなら同じ種類の動きとだけまとめる。
+    pub rewrite_wide: bool,
+    /// 持ち替えを他の動きとまとめて書き直してよいか。
+    /// falseなら持ち替えどうしだけをまとめる。
+    pub rewrite_rotations: bool,
+}
+
+// 軸ごとの動き。[上の層, 真ん中の層, 下の層]をどちら向きに回すか。
+// 順番は面、反対の面、スライス、2層回し、反対の2層回し、持ち替え。
+const N_KINDS: usize = 6;
+
+struct Generator {
+    layer: Layer,
+    wide: bool,
+    effect: [i8; 3],
+}
+
+const fn generator(layer: Layer, wide: bool, effect: [i8; 3]) -> Generator {
+    Generator { layer, wide, effect }
+}
+
+static AXIS_GENERATORS: [[Generator; N_KINDS]; 3] = [
+    [
+        generator(Layer::R, false, [1, 0, 0]),
+        generator(Layer::L, false, [0, 0, -1]),
+        generator(Layer::M, false, [0, -1, 0]),
+        generator(Layer::R, true, [1, 1, 0]),
+        generator(Layer::L, true, [0, -1, -1]),
+        generator(Layer::X, false, [1, 1, 1]),
+    ],
+    [
+        generator(Layer::U, false, [1, 0, 0]),
+        generator(Layer::D, false, [0, 0, -1]),
+        generator(Layer::E, false, [0, -1, 0]),
+        generator(Layer::U, true, [1, 1, 0]),
+        generator(Layer::D, true, [0, -1, -1]),
+        generator(Layer::Y, false, [1, 1, 1]),
+    ],
+    [
+        generator(Layer::F, false, [1, 0, 0]),
+        generator(Layer::B, false, [0, 0, -1]),
+        generator(Layer::S, false, [0, 1, 0]),
+        generator(Layer::F, true, [1, 1, 0]),
+        generator(Layer::B, true, [0, -1, -1]),
+        generator(Layer::Z, false, [1, 1, 1]),
+    ],
+];
+
+// 動きの軸と、AXIS_GENERATORSの添字
+fn classify(mv: Move) -> (usize, usize) {
+    AXIS_GENERATORS.iter().enumerate()
+        .find_map(|(axis, gens)| {
+            gens.iter().position(|g| g.layer == mv.layer() && g.wide == mv.is_wide()).map(|k| (axis, k))
+        })
+        .unwrap()
+}
+
+// 同じ軸の動きの列を、optionsで最も短い同じ動きに書き直す。
+fn simplify_axis(axis: usize, mvs: &[Move], options: &SimplifyOptions) -> Vec<Move> {
+    let gens = &AXIS_GENERATORS[axis];
+
+    let rewritable = |k: usize| match k {
+        0 | 1 => true,
+        5 => options.rewrite_rotations,
+        _ => options.rewrite_wide,
+    };
+
+    // 書き直せる動きは各層を回す量にまとめ、それ以外は種類ごとにまとめる。
+    let mut target = [0_i8; 3];
+    let mut fixed = [0_u8; N_KINDS];
+
+    for mv in mvs {
+        let (_, k) = classify(*mv);
+
+        if rewritable(k) {
+            for (t, e) in target.iter_mut().zip(gens[k].effect) {
+                *t = (*t + e * mv.amount() as i8).rem_euclid(4);
+            }
+        } else {
+            fixed[k] = (fixed[k] + mv.amount()) % 4;
+        }
+    }
+
+    // 書き直せる種類の回す量の組み合わせを全部試す。
+    // 手数、動きの数、前の種類を優先する順で最小のものを選ぶ。
+    let kinds: Vec<usize> = (0..N_KINDS).filter(|k| rewritable(*k)).collect();
+    let mut best: Option<((usize, usize, usize), [u8; N_KINDS])> = None;
+
+    for code in 0..4_usize.pow(kinds.len() as u32) {
+        let mut amounts = [0_u8; N_KINDS];
+        let mut effect = [0_i8; 3];
+
+        for (i, k) in kinds.iter().enumerate() {
+            let a = (code >> (2 * i) & 3) as u8;
+            amounts[*k] = a;
+
+            for (e, g) in effect.iter_mut().zip(gens[*k].effect) {
+                *e = (*e + g * a as i8).rem_euclid(4);
+            }
+        }
+
+        if effect != target {
+            continue;
+        }
+
+        let used = kinds.iter().filter(|k| amounts[**k] != 0);
+        let key = (
+            used.clone().map(|k| options.metric.cost(make_move(&gens[*k], amounts[*k]))).sum(),
+            used.clone().count(),
+            used.sum(),
+        );
+
+        if best.as_ref().is_none_or(|(b, _)| key < *b) {
+            best = Some((key, amounts));
+        }
+    }
+
+    let (_, mut amounts) = best.unwrap();
+
+    for (a, f) in amounts.iter_mut().zip(fixed) {
+        *a = (*a + f) % 4;
+    }
+
+    (0..N_KINDS).filter(|k| amounts[*k] != 0).map(|k| make_move(&gens[k], amounts[k])).collect()
+}
+
+// 動きを面の動きと持ち替えに分ける。
+fn split_rotation(mv: Move) -> (Vec<Move>, Option<Move>) {
+    let a = mv.amount();
+    let turn = |layer: Layer, amount: u8| Move::new(layer, amount).unwrap();
+
+    let (faces, rot) = match (mv.layer(), mv.is_wide()) {
+        (Layer::X | Layer::Y | Layer::Z, _) => return (vec![], Some(mv)),
+        (_, false) if mv.layer().is_face() => return (vec![mv], None),
+        (Layer::R, true) => (vec![turn(Layer::L, a)], turn(Layer::X, a)),
+        (Layer::L, true) => (vec![turn(Layer::R, a)], turn(Layer::X, 4 - a)),
+        (Layer::U, true) => (vec![turn(Layer::D, a)], turn(Layer::Y, a)),
+        (Layer::D, true) => (vec![turn(Layer::U, a)], turn(Layer::Y, 4 - a)),
+        (Layer::F, true) => (vec![turn(Layer::B, a)], turn(Layer::Z, a)),
+        (Layer::B, true) => (vec![turn(Layer::F, a)], turn(Layer::Z, 4 - a)),
+        (Layer::M, _) => (vec![turn(Layer::R, a), turn(Layer::L, 4 - a)], turn(Layer::X, 4 - a)),
+        (Layer::E, _) => (vec![turn(Layer::U, a), turn(Layer::D, 4 - a)], turn(Layer::Y, 4 - a)),
+        (_, _) => (vec![turn(Layer::F, 4 - a), turn(Layer::B, a)], turn(Layer::Z, a)),  // S
+    };
+
+    (faces, Some(rot))
+}
+
+fn make_move(g: &Generator, amount: u8) -> Move {
+    if g.wide { Move::new_wide(g.layer, amount) } else { Move::new(g.layer, amount) }.unwrap()
+}
+
+/// 展開済みの手順
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Algorithm {
+    moves: Vec<Move>,
+}
+
+impl Algorithm {
+    pub fn new(moves: Vec<Move>) -> Algorithm {
+        Algorithm { moves }
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// 動きの数
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// metricで数えた手数
+    pub fn count(&self, metric: Metric) -> usize {
+        self.moves.iter().map(|mv| metric.cost(*mv)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// 逆の手順
+    pub fn inverse(&self) -> Algorithm {
+        Algorithm { moves: self.moves.iter().rev().map(|mv| mv.inverse()).collect() }
+    }
+
+    /// axisに垂直な面で鏡に映した手順。Axis::XならRとL'を入れ替える。
+    /// 結果の状態も元の手順の状態を鏡に映したものになる。
+    pub fn mirror(&self, axis: Axis) -> Algorithm {
+        let s = moves::reflection(axis);
+        Algorithm { moves: self.moves.iter().map(|mv| mv.conjugate(s)).collect() }
+    }
+
+    /// rotationで持ち替えた後に同じ動きになる手順。
+    /// rotationの後にこの手順を回すのは、元の手順の後にrotationで持ち替えるのと同じ。
+    /// e.g. yで持ち替えるとRはFになる。
+    pub fn rotate(&self, rotation: Rotation) -> Algorithm {
+        let s = rotation.to_state();
+        Algorithm { moves: self.moves.iter().map(|mv| mv.conjugate(&s)).collect() }
+    }
+
+    /// 持ち替えを含まない面を回す動きだけの手順と、最後の向きに直す。
+    /// 2層回しとスライスは面の動きと持ち替えに分け(e.g. Rw = L x, M = R L' x')、
+    /// 持ち替えの後の動きは持ち替える前の向きでの動きに書き直す。
+    /// 返した手順の後に返した持ち替えをすると、元の手順と同じ状態になる。
+    pub fn remove_rotations(&self) -> (Algorithm, Rotation) {
+        let mut rot = State::new_solved();  // それまでの持ち替え
+        let mut rot_prime = State::new_solved();
+        let mut moves = vec![];
+
+        for mv in &self.moves {
+            let (faces, r) = split_rotation(*mv);
+
+            for f in faces {
+                moves.push(f.conjugate(&rot_prime));
+            }
+
+            if let Some(r) = r {
+                rot = rot.apply_move(r);
+                rot_prime = rot.get_prime();
+            }
+        }
+
+        (Algorithm { moves }, Rotation::from_state(&rot).unwrap())
+    }
+
+    /// 同じ軸の動きをまとめて短くした手順を返す。
+    /// 並んでいる同じ軸の動きはoptions.metricで最も短い書き方にし、打ち消し合う動きは消す。
+    /// 消えた動きの前後が同じ軸になった場合もまとめる。
+    pub fn simplify(&self, options: &SimplifyOptions) -> Algorithm {
+        let mut groups: Vec<(usize, Vec<Move>)> = vec![];
+
+        for mv in &self.moves {
+            let (axis, _) = classify(*mv);
+
+            match groups.last_mut() {
+                Some((a, mvs)) if *a == axis => {
+                    mvs.push(*mv);
+                    *mvs = simplify_axis(axis, mvs, options);
+
+                    if mvs.is_empty() {
+                        groups.pop();
+                    }
+                }
+                _ => {
+                    let mvs = simplify_axis(axis, &[*mv], options);
+
+                    if !mvs.is_empty() {
+                        groups.push((axis, mvs));
+                    }
+                }
+            }
+        }
+
+        Algorithm { moves: groups.into_iter().flat_map(|(_, mvs)| mvs).collect() }
+    }
+}
+
+impl From<Vec<Move>> for Algorithm {
+    fn from(moves: Vec<Move>) -> Algorithm {
+        Algorithm { moves }
+    }
+}
+
+impl From<&Sequence> for Algorithm {
+    fn from(seq: &Sequence) -> Algorithm {
+        Algorithm { moves: seq.moves() }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Algorithm, ParseError> {
+        Ok(Algorithm::from(&notation::parse(s)?))
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, mv) in self.moves.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+
+            write!(f, "{}", mv)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl State {
+    /// 手順でキューブを動かす。定義からわかるがselfは変化しない。
+    pub fn apply_algorithm(&self, alg: &Algorithm) -> State {
+        alg.moves.iter().fold(*self, |st, mv| st.apply_move(*mv))
+    }
+
+    /// axisに垂直な面で鏡に映した状態。Algorithm::mirrorした手順で作った状態と同じになる。
+    pub fn mirror(&self, axis: Axis) -> State {
+        let s = moves::reflection(axis);
+        s.apply(self).apply(s)
+    }
+
+    /// rotationで持ち替えた向きから見た状態。Algorithm::rotateした手順で作った状態と同じになる。
+    pub fn rotate(&self, rotation: Rotation) -> State {
+        let s = rotation.to_state();
+        s.get_prime().apply(self).apply(&s)
+    }
+}
@@ -11,7 +11,7 @@ fn main() {
         .chain([51, 48, 45, 52, 49, 46, 53, 50, 47]).enumerate() {
         x_arr[i] = v;
     }
-    let x = State::new(Box::new(x_arr));
+    let x = State::new(x_arr);
     moves.insert(String::from("x"), x);
 
     let mut y_arr = [0_u8; 54];
@@ -19,7 +19,7 @@ fn main() {
         .chain([33, 30, 27, 34, 31, 28, 35, 32, 29]).chain(18..27).chain(36..45).enumerate() {
         y_arr[i] = v;
     }
-    let y = State::new(Box::new(y_arr));
+    let y = State::new(y_arr);
     moves.insert(String::from("y"), y);
 
     let solved = State::new_solved();
@@ -59,27 +59,27 @@ fn main() {
 
 
     let uw = solved.apply_arg_moves(&moves, "U E E E").unwrap();
-    moves.insert(String::from("u"), uw.clone());
+    moves.insert(String::from("u"), uw);
     moves.insert(String::from("Uw"), uw);
 
     let fw = solved.apply_arg_moves(&moves, "F S").unwrap();
-    moves.insert(String::from("f"), fw.clone());
+    moves.insert(String::from("f"), fw);
     moves.insert(String::from("Fw"), fw);
 
     let rw = solved.apply_arg_moves(&moves, "R M M M").unwrap();
-    moves.insert(String::from("r"), rw.clone());
+    moves.insert(String::from("r"), rw);
     moves.insert(String::from("Rw"), rw);
 
     let bw = solved.apply_arg_moves(&moves, "B S S S").unwrap();
-    moves.insert(String::from("b"), bw.clone());
+    moves.insert(String::from("b"), bw);
     moves.insert(String::from("Bw"), bw);
 
     let lw = solved.apply_arg_moves(&moves, "L M").unwrap();
-    moves.insert(String::from("l"), lw.clone());
+    moves.insert(String::from("l"), lw);
     moves.insert(String::from("Lw"), lw);
 
     let dw = solved.apply_arg_moves(&moves, "D E").unwrap();
-    moves.insert(String::from("d"), dw.clone());
+    moves.insert(String::from("d"), dw);
     moves.insert(String::from("Dw"), dw);
 
     let names = [
@@ -1,119 +1,64 @@
+use std::env;
 use std::time;
 
+use cube::solver::cache::TableCache;
+use cube::solver::optimal::OptimalSolver;
+use cube::solver::{two_phase, SolveOptions};
 use cube::State;
 
-// 反対面
-macro_rules! inv_face {
-    ($face:expr) => {
-        match $face {
-            'U' => 'D',
-            'D' => 'U',
-            'L' => 'R',
-            'R' => 'L',
-            'F' => 'B',
-            'B' => 'F',
-            _ => panic!("不正な面"),
-        }
-    };
-}
-
-// 前の1手を考慮して次の1手として使える操作であるかを判定する
-// - 同じ面は連続して回さない (e.g. R' R2 は不可)
-// - 対面を回すときは順序を固定する (e.g. D Uは良いが、U Dは不可)
-fn is_move_available(prev_move: Option<&String>, cur_move: &str) -> bool {
-    if prev_move.is_none() {
-        return true  // 最初の一手はどの操作も可能
-    }
-
-    let prev_face = prev_move.unwrap().chars().next().unwrap();  // 1手前で回した面
-    let cur_face = cur_move.chars().next().unwrap();  // 現在回そうとしている面
-
-    if prev_face == cur_face {
-        // 同一面は不可能
-        return false;
-    }
-
-    if inv_face!(prev_face) == cur_face {
-        return prev_face < cur_face;  // 対面のときは、辞書順なら可
-    }
-
-    true
-}
-
-// 探索木から探索の必要のない幹を刈る
-// trueを返したらそれ以上探索しないでいい
-fn miki_prune(state: &State, depth: i32) -> bool {
-    let cc = state.count_solved0_corners();
-    let ce = state.count_solved0_edges();
-
-    match depth {
-        1 => cc < 4 || ce < 8,
-        2 => ce < 4,
-        3 => ce < 2,
-        _ => false,
-    }
-}
-
-const MOVE_NAMES: [&str; 18] = [
-"U", "F", "R", "D", "B", "L",
-"U2", "F2", "R2", "D2", "B2", "L2",
-"U'", "F'", "R'", "D'", "B'", "L'",
-];
-
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
 
-fn depth_limited_search(state: &State, solution: &mut Vec<String>, depth: i32) -> bool {
-    if depth == 0 && state.is_solved0() {
-        return true;
-    }
+    // --optimalを指定すると最短の解を求める
+    let optimal = args.first().is_some_and(|a| a == "--optimal");
 
-    if depth == 0 {
-        return false;
+    if optimal {
+        args.remove(0);
     }
 
-    if miki_prune(state, depth) {
-        return false
-    }
+    let scramble = if args.is_empty() { String::from("R U R' F2 D2 L") } else { args.join(" ") };
 
-    for move_name in MOVE_NAMES {
-        if !is_move_available(solution.last(), move_name) {
-            continue;
+    let scrambled_state = match State::new_solved().apply_moves(&scramble) {
+        Ok(st) => st,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
         }
+    };
 
-        solution.push(move_name.to_string());
-
-        if depth_limited_search(&(state * move_name), solution, depth - 1) {
-            return true;
-        }
-
-        solution.pop();
-    }
-
-    false
-}
+    // CUBE_TABLE_DIRを指定すると表をそのディレクトリに保存して再利用する
+    let cache = env::var_os("CUBE_TABLE_DIR").map(TableCache::new);
 
-fn start_search(state: &State, max_length: i32) -> Option<Vec<String>> {
-    let mut solution = vec![];
+    let now = time::Instant::now();
 
-    for depth in 0..max_length {
-        println!("Start searching length {}", depth);
-        if depth_limited_search(state, &mut solution, depth) {
-            return Some(solution);
+    let result = if optimal {
+        let solver = match &cache {
+            Some(cache) => OptimalSolver::with_cache(&Default::default(), cache),
+            None => OptimalSolver::default(),
+        };
+        println!("Tables: {:?}", now.elapsed());
+
+        let now = time::Instant::now();
+        let result = solver.solve(&scrambled_state, &SolveOptions::optimal());
+        println!("{:?}", now.elapsed());
+
+        result
+    } else {
+        match &cache {
+            Some(cache) => two_phase::init_with_cache(cache),
+            None => two_phase::init(),
         }
-    }
+        println!("Tables: {:?}", now.elapsed());
 
-    None
-}
+        let now = time::Instant::now();
+        let result = two_phase::solve(&scrambled_state, &SolveOptions::two_phase());
+        println!("{:?}", now.elapsed());
 
-fn main() {
-    let scramble = "R U R' F2 D2 L";
-    let scrambled_state = &cube::State::new_solved() * scramble;
-
-    let now = time::Instant::now();
+        result
+    };
 
-    match start_search(&scrambled_state, 7) {
-        Some(v) => println!("{:?}", v),
-        None => println!("Solution not found"),
+    match result {
+        Ok(v) => println!("{} ({})", v.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" "), v.len()),
+        Err(e) => println!("{}", e),
     }
-
-    println!("{:?}", now.elapsed());
 }
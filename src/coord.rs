@@ -0,0 +1,293 @@
+//! CubieStateの一部を整数で表す座標。探索の移動表や枝刈り表の添字に使う。
+//! 座標をすべて組み合わせると、面を回すだけで作れる状態に0から順に番号をつけられる。
+
+use crate::cubie::{CubieState, NUM_CORNERS, NUM_EDGES};
+use crate::solvable::{parity, SolvableError};
+use crate::State;
+
+/// コーナーの向きの座標の数 (3^7)
+pub const N_TWIST: usize = 2187;
+/// エッジの向きの座標の数 (2^11)
+pub const N_FLIP: usize = 2048;
+/// UD面の間のスライスにある4つのエッジの位置の座標の数 (12C4)
+pub const N_SLICE: usize = 495;
+/// スライスの4つのエッジの位置と順番の座標の数 (12C4 * 4!)
+pub const N_SLICE_SORTED: usize = 11880;
+/// スライスの4つのエッジの順番の座標の数 (4!)
+pub const N_SLICE_PERM: usize = 24;
+/// コーナーの置換の座標の数 (8!)
+pub const N_CORNERS: usize = 40320;
+/// U面とD面の8つのエッジの置換の座標の数 (8!)
+pub const N_UD_EDGES: usize = 40320;
+/// 12個のエッジの置換の座標の数 (12!)
+pub const N_EDGES: usize = 479001600;
+/// 面を回すだけで作れる状態の数 (8! * 12! * 3^7 * 2^11 / 2)
+pub const N_STATES: u128 = 43252003274489856000;
+
+// スライスのエッジ(FR, FL, BL, BR)
+const SLICE_EDGES: [u8; 4] = [8, 9, 10, 11];
+// スライス以外のエッジ
+const OTHER_EDGES: [u8; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+
+// 組み合わせの数 nCk
+fn c_nk(n: usize, k: usize) -> usize {
+    if n < k {
+        return 0;
+    }
+
+    let k = k.min(n - k);
+    let mut c = 1;
+
+    for i in 0..k {
+        c = c * (n - i) / (i + 1);
+    }
+
+    c
+}
+
+// a[0..=j]を左に回転する。
+fn rotate_left(a: &mut [u8], j: usize) {
+    a[0..=j].rotate_left(1);
+}
+
+// a[0..=j]を右に回転する。
+fn rotate_right(a: &mut [u8], j: usize) {
+    a[0..=j].rotate_right(1);
+}
+
+// a[i] == iとなるまで回転した回数から置換の番号を求める。
+fn perm_to_index(a: &mut [u8], offset: u8) -> usize {
+    let mut b = 0;
+
+    for j in (1..a.len()).rev() {
+        let mut k = 0;
+
+        while a[j] != j as u8 + offset {
+            rotate_left(a, j);
+            k += 1;
+        }
+
+        b = (j + 1) * b + k;
+    }
+
+    b
+}
+
+// perm_to_indexの逆
+fn index_to_perm(a: &mut [u8], mut idx: usize) {
+    for j in 0..a.len() {
+        let k = idx % (j + 1);
+        idx /= j + 1;
+
+        for _ in 0..k {
+            rotate_right(a, j);
+        }
+    }
+}
+
+impl CubieState {
+    /// コーナーの向きの座標 (0..2187)
+    pub fn twist(&self) -> usize {
+        self.co[0..NUM_CORNERS - 1].iter().fold(0, |t, v| t * 3 + *v as usize)
+    }
+
+    pub fn set_twist(&mut self, mut twist: usize) {
+        let mut sum = 0;
+
+        for i in (0..NUM_CORNERS - 1).rev() {
+            self.co[i] = (twist % 3) as u8;
+            sum += self.co[i];
+            twist /= 3;
+        }
+
+        self.co[NUM_CORNERS - 1] = (3 - sum % 3) % 3;
+    }
+
+    /// エッジの向きの座標 (0..2048)
+    pub fn flip(&self) -> usize {
+        self.eo[0..NUM_EDGES - 1].iter().fold(0, |t, v| t * 2 + *v as usize)
+    }
+
+    pub fn set_flip(&mut self, mut flip: usize) {
+        let mut sum = 0;
+
+        for i in (0..NUM_EDGES - 1).rev() {
+            self.eo[i] = (flip % 2) as u8;
+            sum += self.eo[i];
+            flip /= 2;
+        }
+
+        self.eo[NUM_EDGES - 1] = sum % 2;
+    }
+
+    /// スライスのエッジの位置と順番の座標 (0..11880)
+    /// 24で割るとスライスのエッジの位置の座標 (0..495)、
+    /// スライスのエッジがスライスにあるときは順番の座標 (0..24) になる。
+    pub fn slice_sorted(&self) -> usize {
+        let mut a = 0;
+        let mut x = 0;
+        let mut edge4 = [0_u8; 4];
+
+        for j in (0..NUM_EDGES).rev() {
+            if SLICE_EDGES.contains(&self.ep[j]) {
+                a += c_nk(NUM_EDGES - 1 - j, x + 1);
+                edge4[3 - x] = self.ep[j];
+                x += 1;
+            }
+        }
+
+        a * N_SLICE_PERM + perm_to_index(&mut edge4, SLICE_EDGES[0])
+    }
+
+    /// スライスのエッジの位置の座標 (0..495)
+    pub fn slice(&self) -> usize {
+        self.slice_sorted() / N_SLICE_PERM
+    }
+
+    pub fn set_slice(&mut self, idx: usize) {
+        self.set_slice_sorted(idx * N_SLICE_PERM);
+    }
+
+    pub fn set_slice_sorted(&mut self, idx: usize) {
+        let mut slice_edges = SLICE_EDGES;
+        index_to_perm(&mut slice_edges, idx % N_SLICE_PERM);
+
+        let mut a = idx / N_SLICE_PERM;
+        let mut x = 4;
+        let mut ep = [None; NUM_EDGES];
+
+        for (j, e) in ep.iter_mut().enumerate() {
+            if x > 0 && a >= c_nk(NUM_EDGES - 1 - j, x) {
+                *e = Some(slice_edges[4 - x]);
+                a -= c_nk(NUM_EDGES - 1 - j, x);
+                x -= 1;
+            }
+        }
+
+        let mut others = OTHER_EDGES.iter();
+
+        for (dst, e) in self.ep.iter_mut().zip(ep) {
+            *dst = e.unwrap_or_else(|| *others.next().unwrap());
+        }
+    }
+
+    /// コーナーの置換の座標 (0..40320)
+    pub fn corners(&self) -> usize {
+        perm_to_index(&mut self.cp.clone(), 0)
+    }
+
+    pub fn set_corners(&mut self, idx: usize) {
+        self.cp = [0, 1, 2, 3, 4, 5, 6, 7];
+        index_to_perm(&mut self.cp, idx);
+    }
+
+    /// U面とD面の8つのエッジの置換の座標 (0..40320)
+    /// 8つのエッジがU面とD面にあるときだけ使える。
+    pub fn ud_edges(&self) -> usize {
+        let mut ep = [0_u8; 8];
+        ep.copy_from_slice(&self.ep[0..8]);
+
+        perm_to_index(&mut ep, 0)
+    }
+
+    pub fn set_ud_edges(&mut self, idx: usize) {
+        let mut ep = OTHER_EDGES;
+        index_to_perm(&mut ep, idx);
+
+        self.ep[0..8].copy_from_slice(&ep);
+        self.ep[8..12].copy_from_slice(&SLICE_EDGES);
+    }
+
+    /// 12個のエッジの置換の座標 (0..479001600)
+    pub fn edges(&self) -> usize {
+        perm_to_index(&mut self.ep.clone(), 0)
+    }
+
+    pub fn set_edges(&mut self, idx: usize) {
+        self.ep = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        index_to_perm(&mut self.ep, idx);
+    }
+
+    /// 状態の番号 (0..N_STATES)。面を回すだけで作れない状態ならエラーを返す。
+    /// エッジの置換の偶奇はコーナーの置換で決まるので、エッジの置換の座標は半分にして使う。
+    pub fn rank(&self) -> Result<u128, SolvableError> {
+        self.check_solvable()?;
+
+        let perm = self.corners() as u128 * (N_EDGES / 2) as u128 + (self.edges() / 2) as u128;
+        Ok((perm * N_TWIST as u128 + self.twist() as u128) * N_FLIP as u128 + self.flip() as u128)
+    }
+
+    /// rankの逆。範囲外ならNone。
+    pub fn unrank(rank: u128) -> Option<CubieState> {
+        if rank >= N_STATES {
+            return None;
+        }
+
+        let mut cs = CubieState::new_solved();
+        cs.set_flip((rank % N_FLIP as u128) as usize);
+        let rank = rank / N_FLIP as u128;
+        cs.set_twist((rank % N_TWIST as u128) as usize);
+        let rank = rank / N_TWIST as u128;
+        cs.set_corners((rank / (N_EDGES / 2) as u128) as usize);
+
+        // 座標の最下位の桁は最後の2つのエッジを入れ替えるかどうかなので、偶奇が合う方を選ぶ
+        let edges = (rank % (N_EDGES / 2) as u128) as usize * 2;
+        cs.set_edges(edges);
+
+        if parity(&cs.ep) != parity(&cs.cp) {
+            cs.set_edges(edges + 1);
+        }
+
+        Some(cs)
+    }
+}
+
+impl State {
+    /// 状態の番号 (0..N_STATES)。持ち替えは無視する。面を回すだけで作れない状態ならエラーを返す。
+    pub fn rank(&self) -> Result<u128, SolvableError> {
+        self.cubie_state()?.rank()
+    }
+
+    /// rankの逆。上が白で前が緑の状態を返す。範囲外ならNone。
+    pub fn unrank(rank: u128) -> Option<State> {
+        CubieState::unrank(rank).map(|cs| cs.to_state())
+    }
+}
+
+#[test]
+fn test_coords() {
+    let mut cs = CubieState::new_solved();
+    assert_eq!((cs.twist(), cs.flip(), cs.slice_sorted(), cs.corners(), cs.ud_edges()), (0, 0, 0, 0, 0));
+
+    for idx in [1, 100, 2186] {
+        cs.set_twist(idx);
+        assert_eq!(cs.twist(), idx);
+    }
+
+    for idx in [1, 100, 2047] {
+        cs.set_flip(idx);
+        assert_eq!(cs.flip(), idx);
+    }
+
+    for idx in [1, 100, 11879] {
+        cs.set_slice_sorted(idx);
+        assert_eq!(cs.slice_sorted(), idx);
+    }
+
+    for idx in [1, 100, 494] {
+        cs.set_slice(idx);
+        assert_eq!(cs.slice(), idx);
+    }
+
+    for idx in [1, 100, 479001599] {
+        cs.set_edges(idx);
+        assert_eq!(cs.edges(), idx);
+    }
+
+    for idx in [1, 100, 40319] {
+        cs.set_corners(idx);
+        assert_eq!(cs.corners(), idx);
+        cs.set_ud_edges(idx);
+        assert_eq!(cs.ud_edges(), idx);
+    }
+}
@@ -0,0 +1,239 @@
+//! キューブをコーナーとエッジの位置と向きで表す。
+
+use std::ops;
+
+use crate::solvable::SolvableError;
+use crate::{State, NUM_P};
+
+pub const NUM_CORNERS: usize = 8;
+pub const NUM_EDGES: usize = 12;
+
+/// コーナーの位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB,
+}
+
+/// エッジの位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    UR, UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR,
+}
+
+/// コーナーのステッカー番号。1番目のステッカーはU面かD面。
+/// 残り2つは外から見て時計回りの順番。
+pub(crate) const CORNER_FACELETS: [[u8; 3]; NUM_CORNERS] = [
+    [8, 18, 11],   // URF
+    [6, 9, 47],    // UFL
+    [0, 45, 38],   // ULB
+    [2, 36, 20],   // UBR
+    [29, 17, 24],  // DFR
+    [27, 53, 15],  // DLF
+    [33, 44, 51],  // DBL
+    [35, 26, 42],  // DRB
+];
+
+/// エッジのステッカー番号。1番目のステッカーはU面かD面、なければF面かB面。
+pub(crate) const EDGE_FACELETS: [[u8; 2]; NUM_EDGES] = [
+    [5, 19],   // UR
+    [7, 10],   // UF
+    [3, 46],   // UL
+    [1, 37],   // UB
+    [32, 25],  // DR
+    [28, 16],  // DF
+    [30, 52],  // DL
+    [34, 43],  // DB
+    [14, 21],  // FR
+    [12, 50],  // FL
+    [41, 48],  // BL
+    [39, 23],  // BR
+];
+
+/// センターのステッカー番号
+pub(crate) const CENTER_FACELETS: [u8; 6] = [4, 13, 22, 31, 40, 49];
+
+/// キューブの状態をコーナーとエッジの置換と向きで表す。
+/// センターは動かないものとする。
+///
+/// cp\[i\]は位置iにあるコーナー、co\[i\]はその向き(0..3)。
+/// ep\[i\]は位置iにあるエッジ、eo\[i\]はその向き(0..2)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CubieState {
+    pub cp: [u8; NUM_CORNERS],
+    pub co: [u8; NUM_CORNERS],
+    pub ep: [u8; NUM_EDGES],
+    pub eo: [u8; NUM_EDGES],
+}
+
+impl Default for CubieState {
+    fn default() -> CubieState {
+        CubieState::new_solved()
+    }
+}
+
+impl CubieState {
+    /// そろった状態を作る。
+    pub fn new_solved() -> CubieState {
+        CubieState {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; NUM_CORNERS],
+            ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            eo: [0; NUM_EDGES],
+        }
+    }
+
+    /// ステッカーの置換から作る。
+    /// センターが動いている場合や、ステッカーがピースとして成り立っていない場合はエラーを返す。
+    pub fn from_state(st: &State) -> Result<CubieState, SolvableError> {
+        let p = st.get_prime().get_p();  // 位置 => ステッカー
+
+        if CENTER_FACELETS.iter().any(|c| p[*c as usize] != *c) {
+            return Err(SolvableError::CentersMoved);
+        }
+
+        let mut cs = CubieState::new_solved();
+
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let (j, ori) = find_corner(p[facelets[0] as usize], p[facelets[1] as usize], p[facelets[2] as usize])
+                .ok_or(SolvableError::InvalidCorner { position: i })?;
+
+            cs.cp[i] = j as u8;
+            cs.co[i] = ori as u8;
+        }
+
+        for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let (j, ori) = find_edge(p[facelets[0] as usize], p[facelets[1] as usize])
+                .ok_or(SolvableError::InvalidEdge { position: i })?;
+
+            cs.ep[i] = j as u8;
+            cs.eo[i] = ori as u8;
+        }
+
+        Ok(cs)
+    }
+
+    /// ステッカーの置換に変換する。
+    pub fn to_state(&self) -> State {
+        let mut p = [0_u8; NUM_P];
+
+        for c in CENTER_FACELETS {
+            p[c as usize] = c;
+        }
+
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let corner = &CORNER_FACELETS[self.cp[i] as usize];
+            let ori = self.co[i] as usize;
+
+            for (k, pos) in facelets.iter().enumerate() {
+                p[corner[(k + 3 - ori) % 3] as usize] = *pos;
+            }
+        }
+
+        for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let edge = &EDGE_FACELETS[self.ep[i] as usize];
+            let ori = self.eo[i] as usize;
+
+            for (k, pos) in facelets.iter().enumerate() {
+                p[edge[(k + ori) % 2] as usize] = *pos;
+            }
+        }
+
+        State::new(p)
+    }
+
+    /// キューブがそろっているならtrueを返す。
+    pub fn is_solved(&self) -> bool {
+        *self == CubieState::new_solved()
+    }
+
+    /// キューブを動かす。定義からわかるがselfは変化しない。
+    pub fn apply(&self, mv: &CubieState) -> CubieState {
+        let mut cs = CubieState::new_solved();
+
+        for i in 0..NUM_CORNERS {
+            let j = mv.cp[i] as usize;
+            cs.cp[i] = self.cp[j];
+            cs.co[i] = (self.co[j] + mv.co[i]) % 3;
+        }
+
+        for i in 0..NUM_EDGES {
+            let j = mv.ep[i] as usize;
+            cs.ep[i] = self.ep[j];
+            cs.eo[i] = (self.eo[j] + mv.eo[i]) % 2;
+        }
+
+        cs
+    }
+
+    /// 指定した動きの逆の動きを取得する。
+    pub fn get_prime(&self) -> CubieState {
+        let mut cs = CubieState::new_solved();
+
+        for i in 0..NUM_CORNERS {
+            let j = self.cp[i] as usize;
+            cs.cp[j] = i as u8;
+            cs.co[j] = (3 - self.co[i]) % 3;
+        }
+
+        for i in 0..NUM_EDGES {
+            let j = self.ep[i] as usize;
+            cs.ep[j] = i as u8;
+            cs.eo[j] = self.eo[i];
+        }
+
+        cs
+    }
+}
+
+// 3枚のステッカーがどのコーナーのものかを調べ、(コーナー, 向き)を返す。
+pub(crate) fn find_corner(s0: u8, s1: u8, s2: u8) -> Option<(usize, usize)> {
+    let stickers = [s0, s1, s2];
+
+    for (j, corner) in CORNER_FACELETS.iter().enumerate() {
+        for ori in 0..3 {
+            if (0..3).all(|k| stickers[k] == corner[(k + 3 - ori) % 3]) {
+                return Some((j, ori));
+            }
+        }
+    }
+
+    None
+}
+
+// 2枚のステッカーがどのエッジのものかを調べ、(エッジ, 向き)を返す。
+pub(crate) fn find_edge(s0: u8, s1: u8) -> Option<(usize, usize)> {
+    for (j, edge) in EDGE_FACELETS.iter().enumerate() {
+        if s0 == edge[0] && s1 == edge[1] {
+            return Some((j, 0));
+        }
+
+        if s0 == edge[1] && s1 == edge[0] {
+            return Some((j, 1));
+        }
+    }
+
+    None
+}
+
+impl TryFrom<&State> for CubieState {
+    type Error = SolvableError;
+
+    fn try_from(st: &State) -> Result<Self, Self::Error> {
+        CubieState::from_state(st)
+    }
+}
+
+impl From<&CubieState> for State {
+    fn from(cs: &CubieState) -> Self {
+        cs.to_state()
+    }
+}
+
+// *演算子でキューブを動かす。
+impl ops::Mul for &CubieState {
+    type Output = CubieState;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.apply(rhs)
+    }
+}
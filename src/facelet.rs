@@ -0,0 +1,211 @@
+//! 他のツールとやりとりするための54文字の面の文字列
+//! (e.g. "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB")を扱う。
+//! 文字列の面の順番はU, R, F, D, L, Bで、各面のステッカーの番号はこのクレートと同じ。
+//! 実物のキューブから読み取った各面の色も同じように状態に変換する。
+
+use std::error::Error;
+use std::fmt;
+
+use crate::cubie::{CENTER_FACELETS, CORNER_FACELETS, EDGE_FACELETS, NUM_CORNERS, NUM_EDGES};
+use crate::solvable::SolvableError;
+use crate::{Color, State, NUM_P};
+
+// このクレートの面の順番(U, F, R, D, B, L)での面の文字
+const FACE_CHARS: [char; 6] = ['U', 'F', 'R', 'D', 'B', 'L'];
+
+// 文字列の面の順番(U, R, F, D, L, B)からこのクレートの面の順番への変換
+const FACELET_ORDER: [usize; 6] = [0, 2, 1, 3, 5, 4];
+
+/// 面の文字列や色から状態を作れない理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceletError {
+    /// 文字列の長さが54でない。
+    InvalidLength(usize),
+    /// U, R, F, D, L, B以外の文字がある。
+    InvalidChar { index: usize, ch: char },
+    /// ある面の文字が9個でない。
+    InvalidCount { face: char, count: usize },
+    /// ある色が9個でない。
+    InvalidColorCount { color: Color, count: usize },
+    /// センターの色がそろっていない。
+    InvalidCenters,
+    /// コーナーの位置に存在しない色の組み合わせがある。
+    InvalidCorner { position: usize },
+    /// エッジの位置に存在しない色の組み合わせがある。
+    InvalidEdge { position: usize },
+    /// 同じ色の組み合わせのコーナーが2つの位置にあり、どちらが正しいかわからない。
+    DuplicateCorner { position: usize, other: usize },
+    /// 同じ色の組み合わせのエッジが2つの位置にあり、どちらが正しいかわからない。
+    DuplicateEdge { position: usize, other: usize },
+    /// 面を回すだけでは作れない。
+    Unsolvable(SolvableError),
+}
+
+impl fmt::Display for FaceletError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FaceletError::InvalidLength(len) => write!(f, "文字列の長さが54でない: {}", len),
+            FaceletError::InvalidChar { index, ch } => write!(f, "無効な文字: {} ({}文字目)", ch, index),
+            FaceletError::InvalidCount { face, count } => write!(f, "{}の数が9でない: {}", face, count),
+            FaceletError::InvalidColorCount { color, count } => write!(f, "{:?}の数が9でない: {}", color, count),
+            FaceletError::InvalidCenters => write!(f, "センターの色が不正"),
+            FaceletError::InvalidCorner { position } => write!(f, "不正なコーナー: {}", position),
+            FaceletError::InvalidEdge { position } => write!(f, "不正なエッジ: {}", position),
+            FaceletError::DuplicateCorner { position, other } => write!(f, "同じコーナーが複数ある: {}, {}", other, position),
+            FaceletError::DuplicateEdge { position, other } => write!(f, "同じエッジが複数ある: {}, {}", other, position),
+            FaceletError::Unsolvable(e) => write!(f, "そろえられない状態: {}", e),
+        }
+    }
+}
+
+impl Error for FaceletError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FaceletError::Unsolvable(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// ステッカーがそろった状態でどの面にあるか
+fn home_face(sticker: u8) -> u8 {
+    sticker / 9
+}
+
+/// 各位置のステッカーがそろった状態でどの面にあったか(= 色)から状態を作る。
+/// 持ち替えでセンターが動いていてもよい。
+pub(crate) fn state_from_faces(faces: &[u8; NUM_P]) -> Result<State, FaceletError> {
+    const NONE: u8 = u8::MAX;
+    let mut p = [NONE; NUM_P];  // ステッカー => 位置
+
+    for c in CENTER_FACELETS {
+        let sticker = faces[c as usize] * 9 + 4;
+
+        if p[sticker as usize] != NONE {
+            return Err(FaceletError::InvalidCenters);
+        }
+
+        p[sticker as usize] = c;
+    }
+
+    let mut corner_pos = [None; NUM_CORNERS];  // コーナー => 見つかった位置
+
+    for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+        let colors = facelets.map(|f| faces[f as usize]);
+
+        let (j, ori) = (0..NUM_CORNERS)
+            .flat_map(|j| (0..3).map(move |ori| (j, ori)))
+            .find(|(j, ori)| (0..3).all(|k| colors[k] == home_face(CORNER_FACELETS[*j][(k + 3 - ori) % 3])))
+            .ok_or(FaceletError::InvalidCorner { position: i })?;
+
+        if let Some(other) = corner_pos[j] {
+            return Err(FaceletError::DuplicateCorner { position: i, other });
+        }
+
+        corner_pos[j] = Some(i);
+
+        for (k, pos) in facelets.iter().enumerate() {
+            p[CORNER_FACELETS[j][(k + 3 - ori) % 3] as usize] = *pos;
+        }
+    }
+
+    let mut edge_pos = [None; NUM_EDGES];  // エッジ => 見つかった位置
+
+    for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+        let colors = facelets.map(|f| faces[f as usize]);
+
+        let (j, ori) = (0..NUM_EDGES)
+            .flat_map(|j| (0..2).map(move |ori| (j, ori)))
+            .find(|(j, ori)| (0..2).all(|k| colors[k] == home_face(EDGE_FACELETS[*j][(k + ori) % 2])))
+            .ok_or(FaceletError::InvalidEdge { position: i })?;
+
+        if let Some(other) = edge_pos[j] {
+            return Err(FaceletError::DuplicateEdge { position: i, other });
+        }
+
+        edge_pos[j] = Some(i);
+
+        for (k, pos) in facelets.iter().enumerate() {
+            p[EDGE_FACELETS[j][(k + ori) % 2] as usize] = *pos;
+        }
+    }
+
+    let st = State::new(p);
+    st.check_solvable().map_err(FaceletError::Unsolvable)?;
+
+    Ok(st)
+}
+
+impl State {
+    /// 面の文字列から状態を作る。
+    /// 各文字はそろった状態でその面にある色を表す(U: 白、F: 緑、R: 赤、D: 黄、B: 青、L: オレンジ)。
+    pub fn from_facelets(s: &str) -> Result<State, FaceletError> {
+        let chars: Vec<char> = s.chars().collect();
+
+        if chars.len() != NUM_P {
+            return Err(FaceletError::InvalidLength(chars.len()));
+        }
+
+        let mut faces = [0_u8; NUM_P];
+        let mut counts = [0_usize; 6];
+
+        for (index, ch) in chars.into_iter().enumerate() {
+            let face = FACE_CHARS.iter().position(|c| *c == ch)
+                .ok_or(FaceletError::InvalidChar { index, ch })?;
+
+            let pos = FACELET_ORDER[index / 9] * 9 + index % 9;
+            faces[pos] = face as u8;
+            counts[face] += 1;
+        }
+
+        for (face, count) in counts.into_iter().enumerate() {
+            if count != 9 {
+                return Err(FaceletError::InvalidCount { face: FACE_CHARS[face], count });
+            }
+        }
+
+        state_from_faces(&faces)
+    }
+
+    /// 実物のキューブから読み取った各面の色から状態を作る。
+    /// 各面の色の並びはget_face_colorsと同じ。
+    pub fn from_face_colors(up: [Color; 9], front: [Color; 9], right: [Color; 9],
+                            down: [Color; 9], back: [Color; 9], left: [Color; 9]) -> Result<State, FaceletError> {
+        let mut faces = [0_u8; NUM_P];
+        let mut counts = [0_usize; 6];
+
+        for (i, color) in [up, front, right, down, back, left].iter().flatten().enumerate() {
+            // 色の順番はそろった状態の面の順番と同じ
+            faces[i] = *color as u8;
+            counts[*color as usize] += 1;
+        }
+
+        for color in [Color::White, Color::Green, Color::Red, Color::Yellow, Color::Blue, Color::Orange] {
+            let count = counts[color as usize];
+
+            if count != 9 {
+                return Err(FaceletError::InvalidColorCount { color, count });
+            }
+        }
+
+        state_from_faces(&faces)
+    }
+
+    /// 面の文字列に変換する。
+    /// 各文字は現在その色のセンターがある面を表すので、持ち替えた状態も持ち替える前と同じ文字列になる。
+    pub fn to_facelets(&self) -> String {
+        let inv = self.get_prime().get_p();  // 位置 => ステッカー
+
+        // 色 => 現在その色のセンターがある面
+        let mut center_faces = [0_usize; 6];
+
+        for (face, c) in CENTER_FACELETS.iter().enumerate() {
+            center_faces[home_face(inv[*c as usize]) as usize] = face;
+        }
+
+        (0..NUM_P).map(|index| {
+            let pos = FACELET_ORDER[index / 9] * 9 + index % 9;
+            FACE_CHARS[center_faces[home_face(inv[pos]) as usize]]
+        }).collect()
+    }
+}
@@ -1,19 +1,31 @@
 //! 3x3x3のキューブパズルをシミュレーションする
 
 pub mod moves;
-
+pub mod cubie;
+pub mod solvable;
+pub mod facelet;
+pub mod notation;
+pub mod algorithm;
+pub mod solver;
+pub mod symmetry;
+pub mod scramble;
+pub mod render;
+pub mod scheme;
+pub mod coord;
+
+use std::fmt;
 use std::ops;
 use std::collections::HashMap;
 
-use colored::*;
+use scheme::{ColorScheme, Mask};
 
 pub const NUM_P: usize = 54;
 
 /// キューブの状態を表す。
 /// キューブの動きも状態で表す。
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct State {
-    p: Box<[u8; NUM_P]>,  // インデックス => 値の置換[上9, 前9, 右9, 下9, 後9, 左9]。pは値を変えてはいけない。
+    p: [u8; NUM_P],  // インデックス => 値の置換[上9, 前9, 右9, 下9, 後9, 左9]。pは値を変えてはいけない。
 }
 
 /// キューブの色
@@ -28,6 +40,7 @@ pub enum Color {
 }
 
 /// キューブの面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Face {
     Up,
     Front,
@@ -37,31 +50,16 @@ pub enum Face {
     Left,
 }
 
-// 色への変換マップ
-static COLOR_MAP: [Color; NUM_P] = [
-    Color::White, Color::White, Color::White, Color::White, Color::White, Color::White, Color::White, Color::White, Color::White,
-    Color::Green, Color::Green, Color::Green, Color::Green, Color::Green, Color::Green, Color::Green, Color::Green, Color::Green,
-    Color::Red, Color::Red, Color::Red, Color::Red, Color::Red, Color::Red, Color::Red, Color::Red, Color::Red,
-    Color::Yellow, Color::Yellow, Color::Yellow, Color::Yellow, Color::Yellow, Color::Yellow, Color::Yellow, Color::Yellow, Color::Yellow,
-    Color::Blue, Color::Blue, Color::Blue, Color::Blue, Color::Blue, Color::Blue, Color::Blue, Color::Blue, Color::Blue,
-    Color::Orange, Color::Orange, Color::Orange, Color::Orange, Color::Orange, Color::Orange, Color::Orange, Color::Orange, Color::Orange,
-];
+// ステッカーからANSIエスケープで色を付けた文字列をつくる。端末かどうかに関係なく色を付ける。
+fn sticker_str(scheme: &ColorScheme, sticker: scheme::Sticker) -> String {
+    let c = scheme.sticker_char(sticker);
 
-// キューブの色から色付きの文字列をつくる。
-macro_rules! color_str {
-    ($color:expr) => {
-        match $color {
-            Color::White => "W".white(),
-            Color::Green => "G".truecolor(0, 155, 72),
-            Color::Red => "R".truecolor(183, 18, 52),
-            Color::Yellow => "Y".truecolor(255, 213, 0),
-            Color::Blue => "B".truecolor(0, 70, 173),
-            Color::Orange => "O".truecolor(255, 88, 0),
-        }
-    };
+    match scheme.sticker_rgb(sticker) {
+        Some((r, g, b)) => format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, c),
+        None => c.to_string(),
+    }
 }
 
-
 /// 上が白で、前が緑の状態のキューブ。
 const SOLVED_P: [u8; NUM_P] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
@@ -103,6 +101,27 @@ fn test_lcm() {
 }
 
 
+/// 持ち替えの違いを無視して比較、ハッシュする状態。上を白、前を緑にした状態で持つ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Canonical(State);
+
+impl Canonical {
+    pub fn new(st: &State) -> Canonical {
+        Canonical(st.canonical())
+    }
+
+    /// 上を白、前を緑にした状態
+    pub fn state(&self) -> &State {
+        &self.0
+    }
+}
+
+impl From<State> for Canonical {
+    fn from(st: State) -> Canonical {
+        Canonical::new(&st)
+    }
+}
+
 impl Default for State {
     fn default() -> State {
         State::new_solved()
@@ -112,21 +131,21 @@ impl Default for State {
 impl State {
     /// 上が白で、前が緑の新しいキューブを作る。
     pub fn new_solved() -> State {
-        State { p: Box::new(SOLVED_P) }
+        State { p: SOLVED_P }
     }
 
     /// 指定した動きを取得する。
-    pub fn get_move(name: moves::MOVES) -> State {
-        State { p: Box::new(moves::MOVES_P[name as usize]) }
+    pub fn get_move(mv: impl Into<moves::Move>) -> State {
+        State { p: moves::MOVES_P[mv.into().index()] }
     }
 
     /// 指定した動きを取得する。
     pub fn get_move_by_name(name: &str) -> Option<State> {
-        moves::get_move(name)
+        name.parse::<moves::Move>().ok().map(State::get_move)
     }
 
     /// 新しいキューブを作る。
-    pub fn new(p: Box<[u8; NUM_P]>) -> State {
+    pub fn new(p: [u8; NUM_P]) -> State {
         let st = State { p };
 
         if !st.is_valid_permutation() {
@@ -137,7 +156,7 @@ impl State {
     }
 
     /// 巡回置換から新しいキューブを作る。
-    pub fn cycles(cp: &Vec<u8>) -> State {
+    pub fn cycles(cp: &[u8]) -> State {
         let mut p = SOLVED_P;
 
         if !cp.is_empty() {
@@ -151,7 +170,7 @@ impl State {
             p[*last as usize] = first;
         }
 
-        let st = State { p: Box::new(p) };
+        let st = State { p };
 
         if !st.is_valid_permutation() {
             panic!("不正な置換: {:?}", st.p);
@@ -173,24 +192,43 @@ impl State {
 
     /// pを取得する。
     pub fn get_p(&self) -> [u8; NUM_P] {
-        *self.p
+        self.p
     }
 
     /// キューブがそろっているならtrueを返す。
     /// 回転記号x, y, z, E, M, Sなどセンターキューブを動かしていない場合のみ使用可能。
     pub fn is_solved0(&self) -> bool {
-        *self.p == SOLVED_P
+        self.p == SOLVED_P
     }
 
-    /// キューブがそろっているならtrueを返す。
+    /// キューブがそろっているならtrueを返す。持ち替えていても、そろっていればtrueになる。
     /// 回転記号x, y, z, E, M, Sなどセンターキューブを動かしていない場合は、
     /// is_solved0で比較したほうが速い。
     pub fn is_solved(&self) -> bool {
-        self.p[1..9].iter().all(|c| COLOR_MAP[*c as usize] == COLOR_MAP[self.p[0] as usize]) &&
-        self.p[10..18].iter().all(|c| COLOR_MAP[*c as usize] == COLOR_MAP[self.p[9] as usize]) &&
-        self.p[19..27].iter().all(|c| COLOR_MAP[*c as usize] == COLOR_MAP[self.p[18] as usize]) &&
-        self.p[28..36].iter().all(|c| COLOR_MAP[*c as usize] == COLOR_MAP[self.p[27] as usize]) &&
-        self.p[37..45].iter().all(|c| COLOR_MAP[*c as usize] == COLOR_MAP[self.p[36] as usize])
+        self.canonical().is_solved0()
+    }
+
+    /// センターの向きから、この状態がどの持ち替えをしたものかを返す。
+    /// センターの配置が持ち替えで作れない場合はNone。
+    pub fn orientation(&self) -> Option<moves::Rotation> {
+        moves::Rotation::all().find(|r| {
+            let rp = r.to_state().get_p();
+            cubie::CENTER_FACELETS.iter().all(|c| rp[*c as usize] == self.p[*c as usize])
+        })
+    }
+
+    /// 持ち替えて上を白、前を緑にした状態を返す。
+    /// センターの配置が持ち替えで作れない場合はそのままの状態を返す。
+    pub fn canonical(&self) -> State {
+        match self.orientation() {
+            Some(r) => self.apply(&r.inverse().to_state()),
+            None => *self,
+        }
+    }
+
+    /// 持ち替えの違いを無視して同じ状態ならtrueを返す。
+    pub fn eq_modulo_rotation(&self, other: &State) -> bool {
+        self.canonical() == other.canonical()
     }
 
     pub fn count_solved0_corners(&self) -> i32 {
@@ -225,29 +263,44 @@ impl State {
             p[i] = mv.p[v as usize];
         }
 
-        State { p: Box::new(p) }
+        State { p }
     }
 
     /// 回転記号を指定してキューブを動かす。定義からわかるがselfは変化しない。
-    pub fn apply_moves(&self, mvs: &str) -> Result<State, String> {
-        let mut p = *self.p;
+    pub fn apply_moves(&self, mvs: &str) -> Result<State, notation::ParseError> {
+        Ok(self.apply_sequence(&notation::parse(mvs)?))
+    }
 
-        for name in mvs.split_whitespace() {
-            if let Some(mv) = State::get_move_by_name(name) {
-                for (i, v) in p.into_iter().enumerate() {
-                    p[i] = mv.p[v as usize]
-                }
-            } else {
-                return Err(format!("無効な操作: {}", name));
+    /// 解析済みの手順でキューブを動かす。定義からわかるがselfは変化しない。
+    pub fn apply_sequence(&self, seq: &notation::Sequence) -> State {
+        let mut p = self.p;
+
+        for mv in seq.moves() {
+            let mv = &moves::MOVES_P[mv.index()];
+
+            for (i, v) in p.into_iter().enumerate() {
+                p[i] = mv[v as usize]
             }
         }
 
-        Ok(State { p: Box::new(p) })
+        State { p }
+    }
+
+    /// 1つの動きでキューブを動かす。定義からわかるがselfは変化しない。
+    pub fn apply_move(&self, mv: moves::Move) -> State {
+        let mv = &moves::MOVES_P[mv.index()];
+        let mut p = [0; NUM_P];
+
+        for (i, v) in self.p.into_iter().enumerate() {
+            p[i] = mv[v as usize];
+        }
+
+        State { p }
     }
 
     /// 回転記号を指定してキューブを動かす。定義からわかるがselfは変化しない。
     pub fn apply_arg_moves(&self, moves: &HashMap<String, State>, mvs: &str) -> Result<State, String> {
-        let mut cube = self.clone();
+        let mut cube = *self;
 
         for name in mvs.split_whitespace() {
             match moves.get(name) {
@@ -267,50 +320,28 @@ impl State {
             p[*v as usize] = i as u8;
         }
 
-        State { p: Box::new(p) }
+        State { p }
     }
 
     /// キューブの状態を端末に出力する。
     /// 端末はtrueカラーに対応している前提で書いている。
     pub fn print(&self) {
-        let up = self.get_face_colors(Face::Up);
-        let left = self.get_face_colors(Face::Left);
-        let front = self.get_face_colors(Face::Front);
-        let right = self.get_face_colors(Face::Right);
-        let back = self.get_face_colors(Face::Back);
-        let down = self.get_face_colors(Face::Down);
-
-        for i in 0..3 {
-            println!("    {}{}{}", color_str!(up[i*3]), color_str!(up[i*3 + 1]), color_str!(up[i*3 + 2]));
-        }
+        self.print_with(&ColorScheme::default(), &Mask::default());
+    }
 
-        for i in 0..3 {
-            println!("{}{}{} {}{}{} {}{}{} {}{}{}",
-                     color_str!(left[i*3]), color_str!(left[i*3 + 1]), color_str!(left[i*3 + 2]),
-                     color_str!(front[i*3]), color_str!(front[i*3 + 1]), color_str!(front[i*3 + 2]),
-                     color_str!(right[i*3]), color_str!(right[i*3 + 1]), color_str!(right[i*3 + 2]),
-                     color_str!(back[i*3]), color_str!(back[i*3 + 1]), color_str!(back[i*3 + 2]));
-        }
+    /// 配色とマスクを指定してキューブの状態を端末に出力する。
+    pub fn print_with(&self, scheme: &ColorScheme, mask: &Mask) {
+        println!("{:#}", self.display_with(scheme, mask));
+    }
 
-        for i in 0..3 {
-            println!("    {}{}{}", color_str!(down[i*3]), color_str!(down[i*3 + 1]), color_str!(down[i*3 + 2]));
-        }
+    /// 配色とマスクを指定して展開図を表示する。`{:#}`で表示すると色が付く。
+    pub fn display_with<'a>(&'a self, scheme: &'a ColorScheme, mask: &'a Mask) -> Net<'a> {
+        Net { state: self, scheme, mask }
     }
 
     /// キューブ面の色を取得する。
     pub fn get_face_colors(&self, face: Face) -> [Color; 9] {
-        let mut colors = [Color::White; 9];
-
-        let start = face as usize * 9;
-        let end = start + 9;
-
-        let p = &self.get_prime().p;
-
-        for (i, pi) in (start..end).enumerate() {
-            colors[i] = COLOR_MAP[p[pi] as usize]
-        }
-
-        colors
+        self.get_face_colors_with(face, &ColorScheme::default())
     }
 
     /// 置換として正しい形式か？
@@ -354,20 +385,83 @@ impl State {
         self.get_cycles().iter().map(|v| v.len()).reduce(lcm).unwrap_or(0)
     }
 
+    /// 巡回置換の積を返す。表示すると巡回置換の記法になる。
+    pub fn cycle_notation(&self) -> Cycles {
+        Cycles(self.get_cycles())
+    }
+
     /// 巡回置換の積を出力する。
     pub fn print_cycles(&self) {
-        let pcp = self.get_cycles();
+        println!("{}", self.cycle_notation());
+    }
+}
 
-        if pcp.is_empty() {
-            println!("()");
-            return
+/// 巡回置換の積。(0 2 8 6)(1 5 7 3)のように表示する。何も動かさないなら()。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycles(pub Vec<Vec<u8>>);
+
+impl fmt::Display for Cycles {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "()");
         }
 
-        for cp in pcp {
-            print!("({})", cp.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+        for cp in &self.0 {
+            write!(f, "({})", cp.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 展開図の表示。面の並びはprintと同じ。`{:#}`で表示するとANSIエスケープで色を付ける。
+pub struct Net<'a> {
+    state: &'a State,
+    scheme: &'a ColorScheme,
+    mask: &'a Mask,
+}
+
+impl fmt::Display for Net<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ansi = f.alternate();
+        let face = |face: Face| self.state.face_stickers(face, self.scheme, self.mask).map(|s| {
+            if ansi {
+                sticker_str(self.scheme, s)
+            } else {
+                self.scheme.sticker_char(s).to_string()
+            }
+        });
+        let (up, left, front, right, back, down) =
+            (face(Face::Up), face(Face::Left), face(Face::Front), face(Face::Right), face(Face::Back), face(Face::Down));
+
+        for i in 0..3 {
+            writeln!(f, "    {}{}{}", up[i*3], up[i*3 + 1], up[i*3 + 2])?;
+        }
+
+        for i in 0..3 {
+            writeln!(f, "{}{}{} {}{}{} {}{}{} {}{}{}",
+                     left[i*3], left[i*3 + 1], left[i*3 + 2],
+                     front[i*3], front[i*3 + 1], front[i*3 + 2],
+                     right[i*3], right[i*3 + 1], right[i*3 + 2],
+                     back[i*3], back[i*3 + 1], back[i*3 + 2])?;
+        }
+
+        for i in 0..3 {
+            write!(f, "    {}{}{}", down[i*3], down[i*3 + 1], down[i*3 + 2])?;
+
+            if i < 2 {
+                writeln!(f)?;
+            }
         }
 
-        println!();
+        Ok(())
+    }
+}
+
+/// 展開図を文字で表示する。`{:#}`で表示するとANSIエスケープで色を付ける。
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.display_with(&ColorScheme::default(), &Mask::default()), f)
     }
 }
 
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::fmt;
 use std::sync::OnceLock;
 
 use crate::State;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MOVES {
     X, X2, XPrime,
     Y, Y2, YPrime,
@@ -25,6 +25,153 @@ pub enum MOVES {
     S, S2, SPrime,
 }
 
+static MOVE_NAMES: [&str; 54] = [
+    "x", "x2", "x'",
+    "y", "y2", "y'",
+    "z", "z2", "z'",
+    "U", "U2", "U'",
+    "F", "F2", "F'",
+    "R", "R2", "R'",
+    "D", "D2", "D'",
+    "B", "B2", "B'",
+    "L", "L2", "L'",
+    "Uw", "Uw2", "Uw'",
+    "Fw", "Fw2", "Fw'",
+    "Rw", "Rw2", "Rw'",
+    "Dw", "Dw2", "Dw'",
+    "Bw", "Bw2", "Bw'",
+    "Lw", "Lw2", "Lw'",
+    "M", "M2", "M'",
+    "E", "E2", "E'",
+    "S", "S2", "S'",
+];
+
+impl MOVES {
+    /// 回転記号を取得する。
+    pub fn name(self) -> &'static str {
+        MOVE_NAMES[self as usize]
+    }
+}
+
+/// 回す層。U, F, R, D, B, Lは2層回しにもできる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    X, Y, Z,
+    U, F, R, D, B, L,
+    M, E, S,
+}
+
+static LAYERS: [Layer; 12] = [
+    Layer::X, Layer::Y, Layer::Z,
+    Layer::U, Layer::F, Layer::R, Layer::D, Layer::B, Layer::L,
+    Layer::M, Layer::E, Layer::S,
+];
+
+impl Layer {
+    /// 面を回す層か
+    pub fn is_face(self) -> bool {
+        matches!(self, Layer::U | Layer::F | Layer::R | Layer::D | Layer::B | Layer::L)
+    }
+}
+
+/// 1つの動き
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    layer: Layer,
+    amount: u8,
+    wide: bool,
+}
+
+impl Move {
+    /// amountは時計回りに90度回す回数で、4で割った余りを使う。余りが0のときはNone。
+    pub fn new(layer: Layer, amount: u8) -> Option<Move> {
+        let amount = amount % 4;
+        (amount != 0).then_some(Move { layer, amount, wide: false })
+    }
+
+    /// 2層回しの動き。面を回す層でなければNone。
+    pub fn new_wide(layer: Layer, amount: u8) -> Option<Move> {
+        let mv = Move::new(layer, amount)?;
+        layer.is_face().then_some(Move { wide: true, ..mv })
+    }
+
+    pub(crate) const fn face(layer: Layer, amount: u8) -> Move {
+        Move { layer, amount, wide: false }
+    }
+
+    pub fn layer(self) -> Layer {
+        self.layer
+    }
+
+    /// 時計回りに90度回す回数 (1..=3)
+    pub fn amount(self) -> u8 {
+        self.amount
+    }
+
+    /// 2層回しか
+    pub fn is_wide(self) -> bool {
+        self.wide
+    }
+
+    /// 逆の動き
+    pub fn inverse(self) -> Move {
+        Move { amount: 4 - self.amount, ..self }
+    }
+
+    // MOVES_Pの添字
+    pub(crate) fn index(self) -> usize {
+        let l = self.layer as usize;
+        let row = if self.wide || !self.layer.is_face() && l > 2 { l + 6 } else { l };
+
+        row * 3 + self.amount as usize - 1
+    }
+}
+
+impl From<MOVES> for Move {
+    fn from(mv: MOVES) -> Move {
+        let (row, amount) = (mv as usize / 3, (mv as usize % 3 + 1) as u8);
+
+        match row {
+            0..=8 => Move { layer: LAYERS[row], amount, wide: false },
+            9..=14 => Move { layer: LAYERS[row - 6], amount, wide: true },
+            _ => Move { layer: LAYERS[row - 6], amount, wide: false },
+        }
+    }
+}
+
+impl From<Move> for MOVES {
+    fn from(mv: Move) -> MOVES {
+        ALL_MOVES[mv.index()]
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", MOVE_NAMES[self.index()])
+    }
+}
+
+static ALL_MOVES: [MOVES; 54] = [
+    MOVES::X, MOVES::X2, MOVES::XPrime,
+    MOVES::Y, MOVES::Y2, MOVES::YPrime,
+    MOVES::Z, MOVES::Z2, MOVES::ZPrime,
+    MOVES::U, MOVES::U2, MOVES::UPrime,
+    MOVES::F, MOVES::F2, MOVES::FPrime,
+    MOVES::R, MOVES::R2, MOVES::RPrime,
+    MOVES::D, MOVES::D2, MOVES::DPrime,
+    MOVES::B, MOVES::B2, MOVES::BPrime,
+    MOVES::L, MOVES::L2, MOVES::LPrime,
+    MOVES::Uw, MOVES::Uw2, MOVES::UwPrime,
+    MOVES::Fw, MOVES::Fw2, MOVES::FwPrime,
+    MOVES::Rw, MOVES::Rw2, MOVES::RwPrime,
+    MOVES::Dw, MOVES::Dw2, MOVES::DwPrime,
+    MOVES::Bw, MOVES::Bw2, MOVES::BwPrime,
+    MOVES::Lw, MOVES::Lw2, MOVES::LwPrime,
+    MOVES::M, MOVES::M2, MOVES::MPrime,
+    MOVES::E, MOVES::E2, MOVES::EPrime,
+    MOVES::S, MOVES::S2, MOVES::SPrime,
+];
+
 pub(super) static MOVES_P: [[u8; 54]; 54] = [
     [44, 43, 42, 41, 40, 39, 38, 37, 36, 0, 1, 2, 3, 4, 5, 6, 7, 8, 20, 23, 26, 19, 22, 25, 18, 21, 24, 9, 10, 11, 12, 13, 14, 15, 16, 17, 35, 34, 33, 32, 31, 30, 29, 28, 27, 51, 48, 45, 52, 49, 46, 53, 50, 47],
     [27, 28, 29, 30, 31, 32, 33, 34, 35, 44, 43, 42, 41, 40, 39, 38, 37, 36, 26, 25, 24, 23, 22, 21, 20, 19, 18, 0, 1, 2, 3, 4, 5, 6, 7, 8, 17, 16, 15, 14, 13, 12, 11, 10, 9, 53, 52, 51, 50, 49, 48, 47, 46, 45],
@@ -82,87 +229,132 @@ pub(super) static MOVES_P: [[u8; 54]; 54] = [
     [0, 1, 2, 52, 49, 46, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 3, 20, 21, 4, 23, 24, 5, 26, 27, 28, 29, 25, 22, 19, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 30, 47, 48, 31, 50, 51, 32, 53],
 ];
 
-static MOVE_MAP: OnceLock<HashMap<String, State>> = OnceLock::new();
-
-pub(super) fn get_move(name: &str) -> Option<State> {
-    let m = MOVE_MAP.get_or_init(|| {
-        let mut m = HashMap::new();
-
-        m.insert(String::from("x"), State::get_move(MOVES::X));
-        m.insert(String::from("x2"), State::get_move(MOVES::X2));
-        m.insert(String::from("x'"), State::get_move(MOVES::XPrime));
-        m.insert(String::from("y"), State::get_move(MOVES::Y));
-        m.insert(String::from("y2"), State::get_move(MOVES::Y2));
-        m.insert(String::from("y'"), State::get_move(MOVES::YPrime));
-        m.insert(String::from("z"), State::get_move(MOVES::Z));
-        m.insert(String::from("z2"), State::get_move(MOVES::Z2));
-        m.insert(String::from("z'"), State::get_move(MOVES::ZPrime));
-        m.insert(String::from("U"), State::get_move(MOVES::U));
-        m.insert(String::from("U2"), State::get_move(MOVES::U2));
-        m.insert(String::from("U'"), State::get_move(MOVES::UPrime));
-        m.insert(String::from("F"), State::get_move(MOVES::F));
-        m.insert(String::from("F2"), State::get_move(MOVES::F2));
-        m.insert(String::from("F'"), State::get_move(MOVES::FPrime));
-        m.insert(String::from("R"), State::get_move(MOVES::R));
-        m.insert(String::from("R2"), State::get_move(MOVES::R2));
-        m.insert(String::from("R'"), State::get_move(MOVES::RPrime));
-        m.insert(String::from("D"), State::get_move(MOVES::D));
-        m.insert(String::from("D2"), State::get_move(MOVES::D2));
-        m.insert(String::from("D'"), State::get_move(MOVES::DPrime));
-        m.insert(String::from("B"), State::get_move(MOVES::B));
-        m.insert(String::from("B2"), State::get_move(MOVES::B2));
-        m.insert(String::from("B'"), State::get_move(MOVES::BPrime));
-        m.insert(String::from("L"), State::get_move(MOVES::L));
-        m.insert(String::from("L2"), State::get_move(MOVES::L2));
-        m.insert(String::from("L'"), State::get_move(MOVES::LPrime));
-        m.insert(String::from("Uw"), State::get_move(MOVES::Uw));
-        m.insert(String::from("Uw2"), State::get_move(MOVES::Uw2));
-        m.insert(String::from("Uw'"), State::get_move(MOVES::UwPrime));
-        m.insert(String::from("u"), State::get_move(MOVES::Uw));
-        m.insert(String::from("u2"), State::get_move(MOVES::Uw2));
-        m.insert(String::from("u'"), State::get_move(MOVES::UwPrime));
-        m.insert(String::from("Fw"), State::get_move(MOVES::Fw));
-        m.insert(String::from("Fw2"), State::get_move(MOVES::Fw2));
-        m.insert(String::from("Fw'"), State::get_move(MOVES::FwPrime));
-        m.insert(String::from("f"), State::get_move(MOVES::Fw));
-        m.insert(String::from("f2"), State::get_move(MOVES::Fw2));
-        m.insert(String::from("f'"), State::get_move(MOVES::FwPrime));
-        m.insert(String::from("Rw"), State::get_move(MOVES::Rw));
-        m.insert(String::from("Rw2"), State::get_move(MOVES::Rw2));
-        m.insert(String::from("Rw'"), State::get_move(MOVES::RwPrime));
-        m.insert(String::from("r"), State::get_move(MOVES::Rw));
-        m.insert(String::from("r2"), State::get_move(MOVES::Rw2));
-        m.insert(String::from("r'"), State::get_move(MOVES::RwPrime));
-        m.insert(String::from("Dw"), State::get_move(MOVES::Dw));
-        m.insert(String::from("Dw2"), State::get_move(MOVES::Dw2));
-        m.insert(String::from("Dw'"), State::get_move(MOVES::DwPrime));
-        m.insert(String::from("d"), State::get_move(MOVES::Dw));
-        m.insert(String::from("d2"), State::get_move(MOVES::Dw2));
-        m.insert(String::from("d'"), State::get_move(MOVES::DwPrime));
-        m.insert(String::from("Bw"), State::get_move(MOVES::Bw));
-        m.insert(String::from("Bw2"), State::get_move(MOVES::Bw2));
-        m.insert(String::from("Bw'"), State::get_move(MOVES::BwPrime));
-        m.insert(String::from("b"), State::get_move(MOVES::Bw));
-        m.insert(String::from("b2"), State::get_move(MOVES::Bw2));
-        m.insert(String::from("b'"), State::get_move(MOVES::BwPrime));
-        m.insert(String::from("Lw"), State::get_move(MOVES::Lw));
-        m.insert(String::from("Lw2"), State::get_move(MOVES::Lw2));
-        m.insert(String::from("Lw'"), State::get_move(MOVES::LwPrime));
-        m.insert(String::from("l"), State::get_move(MOVES::Lw));
-        m.insert(String::from("l2"), State::get_move(MOVES::Lw2));
-        m.insert(String::from("l'"), State::get_move(MOVES::LwPrime));
-        m.insert(String::from("M"), State::get_move(MOVES::M));
-        m.insert(String::from("M2"), State::get_move(MOVES::M2));
-        m.insert(String::from("M'"), State::get_move(MOVES::MPrime));
-        m.insert(String::from("E"), State::get_move(MOVES::E));
-        m.insert(String::from("E2"), State::get_move(MOVES::E2));
-        m.insert(String::from("E'"), State::get_move(MOVES::EPrime));
-        m.insert(String::from("S"), State::get_move(MOVES::S));
-        m.insert(String::from("S2"), State::get_move(MOVES::S2));
-        m.insert(String::from("S'"), State::get_move(MOVES::SPrime));
-
-        m
+static ROTATIONS: OnceLock<Vec<State>> = OnceLock::new();
+
+/// 持ち替えによる24通りの向きを取得する。最初の要素は何もしない動き。
+pub(crate) fn rotations() -> &'static [State] {
+    ROTATIONS.get_or_init(|| {
+        let gens = [State::get_move(MOVES::X), State::get_move(MOVES::Y)];
+        let mut rots = vec![State::new_solved()];
+        let mut i = 0;
+
+        while i < rots.len() {
+            for g in &gens {
+                let st = rots[i].apply(g);
+
+                if !rots.contains(&st) {
+                    rots.push(st);
+                }
+            }
+
+            i += 1;
+        }
+
+        rots
+    })
+}
+
+/// 持ち替えの24通りのどれか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation(u8);
+
+impl Rotation {
+    /// 何もしない持ち替え
+    pub fn identity() -> Rotation {
+        Rotation(0)
+    }
+
+    /// 24通りの持ち替え
+    pub fn all() -> impl Iterator<Item = Rotation> {
+        (0..rotations().len() as u8).map(Rotation)
+    }
+
+    /// 持ち替えの動き(x, y, z)の列を1つの持ち替えにする。持ち替え以外の動きがあればNone。
+    pub fn from_moves(mvs: &[Move]) -> Option<Rotation> {
+        if mvs.iter().any(|mv| !matches!(mv.layer, Layer::X | Layer::Y | Layer::Z)) {
+            return None;
+        }
+
+        let st = mvs.iter().fold(State::new_solved(), |st, mv| st.apply_move(*mv));
+        Rotation::from_state(&st)
+    }
+
+    pub(crate) fn from_state(st: &State) -> Option<Rotation> {
+        rotations().iter().position(|r| r == st).map(|i| Rotation(i as u8))
+    }
+
+    /// 持ち替えを表す状態
+    pub fn to_state(self) -> State {
+        rotations()[self.0 as usize]
+    }
+
+    /// 逆の持ち替え
+    pub fn inverse(self) -> Rotation {
+        Rotation::from_state(&self.to_state().get_prime()).unwrap()
+    }
+
+    /// この持ち替えになる最も短い持ち替えの動き(x, y, z)の列。2手以内になる。
+    pub fn moves(self) -> Vec<Move> {
+        let singles: Vec<Move> = [Layer::X, Layer::Y, Layer::Z].into_iter()
+            .flat_map(|l| (1..4).map(move |a| Move { layer: l, amount: a, wide: false }))
+            .collect();
+
+        let candidates = std::iter::once(vec![])
+            .chain(singles.iter().map(|m| vec![*m]))
+            .chain(singles.iter().flat_map(|m1| singles.iter().map(move |m2| vec![*m1, *m2])));
+
+        candidates.into_iter().find(|mvs| Rotation::from_moves(mvs) == Some(self)).unwrap()
+    }
+}
+
+/// キューブの軸。鏡映では軸に垂直な面で映す。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// RとLを結ぶ軸
+    X,
+    /// UとDを結ぶ軸
+    Y,
+    /// FとBを結ぶ軸
+    Z,
+}
+
+static REFLECTIONS: OnceLock<[State; 3]> = OnceLock::new();
+
+/// 軸に垂直な面での鏡映を表す置換
+pub(crate) fn reflection(axis: Axis) -> &'static State {
+    let r = REFLECTIONS.get_or_init(|| {
+        // RとLを入れ替え、各面の列を左右反転する
+        let mut p = [0_u8; 54];
+
+        for (i, v) in p.iter_mut().enumerate() {
+            let (face, r, c) = (i / 9, i % 9 / 3, i % 3);
+            let face = match face { 2 => 5, 5 => 2, f => f };
+            *v = (face * 9 + r * 3 + 2 - c) as u8;
+        }
+
+        let x = State::new(p);
+
+        // 持ち替えで軸を移す
+        let conj = |m: MOVES| {
+            let r = State::get_move(m);
+            r.get_prime().apply(&x).apply(&r)
+        };
+
+        let (y, z) = (conj(MOVES::Z), conj(MOVES::Y));
+
+        [x, y, z]
     });
 
-    m.get(name).cloned()
+    &r[axis as usize]
+}
+
+impl Move {
+    /// sで共役を取った動き(sの逆、self、sの順に動かすのと同じ動き)
+    pub(crate) fn conjugate(self, s: &State) -> Move {
+        let st = s.get_prime().apply(&State::get_move(self)).apply(s);
+
+        ALL_MOVES.iter().map(|m| Move::from(*m))
+            .find(|m| State::get_move(*m) == st)
+            .unwrap()
+    }
 }
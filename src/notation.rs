@@ -0,0 +1,485 @@
+//! 回転記号の文字列(e.g. "R U R'U' (R2' F)")を解析する。
+//!
+//! 動きの間の空白は省略できる。回す量は"2", "3"と"'"の組み合わせで書き、
+//! "R2'"と"R'2"はどちらもR2、"R3"はR'、"R3'"はRになる。
+//! 括弧でまとめた手順は後ろに回数を書くと繰り返す(e.g. "(R U R' U')3")。
+//! 交換子"[A, B]"はA B A' B'、共役"[A: B]"はA B A'に展開する。
+//! 繰り返す回数は[`MAX_REPEAT`]まで、展開した手順の長さは[`MAX_MOVES`]まで、
+//! 括弧の入れ子は[`MAX_DEPTH`]までしか受け付けない。
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+
+pub use crate::moves::{Layer, Move};
+
+/// 繰り返す回数の上限
+pub const MAX_REPEAT: u32 = 1000;
+
+/// 展開した手順の長さの上限
+pub const MAX_MOVES: usize = 10_000;
+
+/// 括弧の入れ子の深さの上限。交換子と共役の"[]"も数える。
+pub const MAX_DEPTH: usize = 100;
+
+impl Layer {
+    // 層の記号の文字と、2層回しの小文字か
+    fn from_char(c: char) -> Option<(Layer, bool)> {
+        let layer = match c {
+            'x' => (Layer::X, false), 'y' => (Layer::Y, false), 'z' => (Layer::Z, false),
+            'U' => (Layer::U, false), 'F' => (Layer::F, false), 'R' => (Layer::R, false),
+            'D' => (Layer::D, false), 'B' => (Layer::B, false), 'L' => (Layer::L, false),
+            'u' => (Layer::U, true), 'f' => (Layer::F, true), 'r' => (Layer::R, true),
+            'd' => (Layer::D, true), 'b' => (Layer::B, true), 'l' => (Layer::L, true),
+            'M' => (Layer::M, false), 'E' => (Layer::E, false), 'S' => (Layer::S, false),
+            _ => return None,
+        };
+
+        Some(layer)
+    }
+}
+
+/// 手順の要素
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Node {
+    Move(Move),
+    /// 括弧でまとめた手順
+    Group(Sequence),
+    /// 交換子[A, B] = A B A' B'
+    Commutator(Sequence, Sequence),
+    /// 共役[A: B] = A B A'
+    Conjugate(Sequence, Sequence),
+    /// 繰り返し。括弧か交換子か共役の後ろに回数を書く。
+    Repeat(Box<Node>, u32),
+}
+
+impl Node {
+    // 展開した動きの数
+    fn expanded_len(&self) -> usize {
+        match self {
+            Node::Move(_) => 1,
+            Node::Group(seq) => seq.expanded_len(),
+            Node::Commutator(a, b) => a.expanded_len().saturating_add(b.expanded_len()).saturating_mul(2),
+            Node::Conjugate(a, b) => a.expanded_len().saturating_mul(2).saturating_add(b.expanded_len()),
+            Node::Repeat(node, count) => node.expanded_len().saturating_mul(*count as usize),
+        }
+    }
+
+    // inverseがtrueなら逆の手順を追加する。
+    fn push_moves(&self, mvs: &mut Vec<Move>, inverse: bool) {
+        match self {
+            Node::Move(mv) => mvs.push(if inverse { mv.inverse() } else { *mv }),
+            Node::Group(seq) => seq.push_moves(mvs, inverse),
+            Node::Commutator(a, b) => {
+                // [A, B]' = [B, A]
+                let (a, b) = if inverse { (b, a) } else { (a, b) };
+
+                a.push_moves(mvs, false);
+                b.push_moves(mvs, false);
+                a.push_moves(mvs, true);
+                b.push_moves(mvs, true);
+            }
+            Node::Conjugate(a, b) => {
+                // [A: B]' = [A: B']
+                a.push_moves(mvs, false);
+                b.push_moves(mvs, inverse);
+                a.push_moves(mvs, true);
+            }
+            Node::Repeat(node, count) => {
+                for _ in 0..*count {
+                    node.push_moves(mvs, inverse);
+                }
+            }
+        }
+    }
+}
+
+/// 手順
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Sequence {
+    pub nodes: Vec<Node>,
+}
+
+impl Sequence {
+    /// 括弧や交換子を展開した動きの列
+    pub fn moves(&self) -> Vec<Move> {
+        let mut mvs = vec![];
+        self.push_moves(&mut mvs, false);
+        mvs
+    }
+
+    fn expanded_len(&self) -> usize {
+        self.nodes.iter().fold(0, |len, node| len.saturating_add(node.expanded_len()))
+    }
+
+    fn push_moves(&self, mvs: &mut Vec<Move>, inverse: bool) {
+        if inverse {
+            for node in self.nodes.iter().rev() {
+                node.push_moves(mvs, true);
+            }
+        } else {
+            for node in &self.nodes {
+                node.push_moves(mvs, false);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::Move(mv) => write!(f, "{}", mv),
+            Node::Group(seq) => write!(f, "({})", seq),
+            Node::Commutator(a, b) => write!(f, "[{}, {}]", a, b),
+            Node::Conjugate(a, b) => write!(f, "[{}: {}]", a, b),
+            Node::Repeat(node, count) => write!(f, "{}{}", node, count),
+        }
+    }
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+
+            write!(f, "{}", node)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Sequence {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Sequence, ParseError> {
+        parse(s)
+    }
+}
+
+impl FromStr for Move {
+    type Err = ParseError;
+
+    /// 1つの動きだけを解析する。前後の空白も許さない。
+    fn from_str(s: &str) -> Result<Move, ParseError> {
+        let mut parser = Parser { s, pos: 0, depth: 0 };
+
+        if parser.peek().and_then(Layer::from_char).is_none() {
+            return Err(parser.error(vec![Token::Move]));
+        }
+
+        let (mv, mut rest) = parser.parse_move();
+
+        if parser.peek().is_some() {
+            rest.push(Token::End);
+            return Err(parser.error(rest));
+        }
+
+        Ok(mv)
+    }
+}
+
+/// 解析エラーで期待していた字句
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    /// 層の記号
+    Move,
+    /// 2層回しの"w"
+    Wide,
+    /// "2"か"3"
+    Amount,
+    /// "'"
+    Prime,
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    /// 交換子の","
+    Comma,
+    /// 共役の":"
+    Colon,
+    /// 繰り返す回数
+    Count,
+    /// 文字列の終わり
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Move => write!(f, "回転記号"),
+            Token::Wide => write!(f, "w"),
+            Token::Amount => write!(f, "2か3"),
+            Token::Prime => write!(f, "'"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::Comma => write!(f, ","),
+            Token::Colon => write!(f, ":"),
+            Token::Count => write!(f, "回数"),
+            Token::End => write!(f, "終わり"),
+        }
+    }
+}
+
+/// 解析エラーの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// 受け付けない文字か文字列の終わり
+    Unexpected,
+    /// 繰り返す回数か展開した手順の長さが上限を超えている。
+    TooLong,
+    /// 括弧の入れ子が深すぎる。
+    TooDeep,
+}
+
+/// 回転記号の文字列を解析できない理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// 解析できなかった位置(バイト単位)
+    pub offset: usize,
+    /// 解析できなかった文字。文字列の終わりならNone。
+    pub found: Option<char>,
+    /// その位置で受け付ける字句。kindがUnexpectedのときだけ使う。
+    pub expected: Vec<Token>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::TooLong => return write!(f, "手順が長すぎる ({}バイト目)", self.offset),
+            ParseErrorKind::TooDeep => return write!(f, "括弧が深すぎる ({}バイト目)", self.offset),
+            ParseErrorKind::Unexpected => {}
+        }
+
+        match self.found {
+            Some(c) => write!(f, "無効な文字: {} ({}バイト目)", c, self.offset)?,
+            None => write!(f, "予期しない終わり ({}バイト目)", self.offset)?,
+        }
+
+        let expected: Vec<String> = self.expected.iter().map(|t| t.to_string()).collect();
+        write!(f, "、期待: {}", expected.join(", "))
+    }
+}
+
+impl Error for ParseError {}
+
+/// 回転記号の文字列を解析する。
+pub fn parse(s: &str) -> Result<Sequence, ParseError> {
+    Parser { s, pos: 0, depth: 0 }.sequence(&[])
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    depth: usize,  // 括弧の入れ子の深さ
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+
+        self.pos != start
+    }
+
+    fn error(&self, expected: Vec<Token>) -> ParseError {
+        ParseError { kind: ParseErrorKind::Unexpected, offset: self.pos, found: self.peek(), expected }
+    }
+
+    // 回数か展開した長さが上限を超えたときのstartの位置のエラー
+    fn too_long(&self, start: usize) -> ParseError {
+        ParseError { kind: ParseErrorKind::TooLong, offset: start, found: self.s[start..].chars().next(), expected: vec![] }
+    }
+
+    // 括弧の中に入る。深すぎればstartの位置のエラーにする。
+    fn enter(&mut self, start: usize) -> Result<(), ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError {
+                kind: ParseErrorKind::TooDeep, offset: start, found: self.s[start..].chars().next(), expected: vec![],
+            });
+        }
+
+        self.depth += 1;
+        self.bump();
+
+        Ok(())
+    }
+
+    fn check_len(&self, start: usize, len: usize) -> Result<(), ParseError> {
+        if len > MAX_MOVES {
+            return Err(self.too_long(start));
+        }
+
+        Ok(())
+    }
+
+    // closersのどれかの文字か文字列の終わりまで読む。閉じる文字は呼び出し側で読む。
+    fn sequence(&mut self, closers: &[char]) -> Result<Sequence, ParseError> {
+        let mut nodes = vec![];
+        let mut cont = vec![];  // 直前の要素に続けられる字句
+        let mut len = 0;  // 展開した長さ
+
+        loop {
+            if self.skip_whitespace() {
+                cont.clear();
+            }
+
+            let start = self.pos;
+
+            match self.peek() {
+                None if closers.is_empty() => return Ok(Sequence { nodes }),
+                Some(c) if closers.contains(&c) => return Ok(Sequence { nodes }),
+                Some('(') => {
+                    self.enter(start)?;
+                    let seq = self.sequence(&[')'])?;
+                    self.bump();
+                    self.depth -= 1;
+
+                    let (node, rest) = self.repeat(Node::Group(seq))?;
+                    len += node.expanded_len();
+                    self.check_len(start, len)?;
+                    nodes.push(node);
+                    cont = rest;
+                }
+                Some('[') => {
+                    self.enter(start)?;
+                    let a = self.sequence(&[',', ':'])?;
+                    let sep = self.peek();
+                    self.bump();
+                    let b = self.sequence(&[']'])?;
+                    self.bump();
+                    self.depth -= 1;
+
+                    let node = if sep == Some(',') { Node::Commutator(a, b) } else { Node::Conjugate(a, b) };
+                    self.check_len(start, node.expanded_len())?;
+                    let (node, rest) = self.repeat(node)?;
+                    len += node.expanded_len();
+                    self.check_len(start, len)?;
+                    nodes.push(node);
+                    cont = rest;
+                }
+                Some(c) if Layer::from_char(c).is_some() => {
+                    let (mv, rest) = self.parse_move();
+
+                    len += 1;
+                    self.check_len(start, len)?;
+                    nodes.push(Node::Move(mv));
+                    cont = rest;
+                }
+                _ => {
+                    cont.extend([Token::Move, Token::OpenParen, Token::OpenBracket]);
+
+                    if closers.is_empty() {
+                        cont.push(Token::End);
+                    }
+
+                    cont.extend(closers.iter().map(|c| match c {
+                        ')' => Token::CloseParen,
+                        ']' => Token::CloseBracket,
+                        ',' => Token::Comma,
+                        _ => Token::Colon,
+                    }));
+
+                    return Err(self.error(cont));
+                }
+            }
+        }
+    }
+
+    // 括弧の後ろに回数があれば繰り返しにする。
+    fn repeat(&mut self, node: Node) -> Result<(Node, Vec<Token>), ParseError> {
+        let start = self.pos;
+
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+
+        if self.pos == start {
+            return Ok((node, vec![Token::Count]));
+        }
+
+        let count = match self.s[start..self.pos].parse::<u32>() {
+            Ok(count) => count,
+            Err(_) => {
+                self.pos = start;
+                return Err(self.error(vec![Token::Count]));
+            }
+        };
+
+        if count > MAX_REPEAT {
+            return Err(self.too_long(start));
+        }
+
+        let node = Node::Repeat(Box::new(node), count);
+        self.check_len(start, node.expanded_len())?;
+
+        Ok((node, vec![]))
+    }
+
+    // 1つの動きを読み、続けて書ける字句も返す。
+    fn parse_move(&mut self) -> (Move, Vec<Token>) {
+        let (layer, lower) = self.peek().and_then(Layer::from_char).unwrap();
+        self.bump();
+
+        // 小文字の2層回しには"w"をつけない
+        let can_widen = layer.is_face() && !lower;
+        let wide = lower || can_widen && self.peek() == Some('w');
+
+        if wide && !lower {
+            self.bump();
+        }
+
+        let mut amount = None;
+        let mut prime = false;
+
+        loop {
+            match self.peek() {
+                Some('2') if amount.is_none() => amount = Some(2),
+                Some('3') if amount.is_none() => amount = Some(3),
+                Some('\'') if !prime => prime = true,
+                _ => break,
+            }
+
+            self.bump();
+        }
+
+        let mut rest = vec![];
+
+        if can_widen && !wide && amount.is_none() && !prime {
+            rest.push(Token::Wide);
+        }
+
+        if amount.is_none() {
+            rest.push(Token::Amount);
+        }
+
+        if !prime {
+            rest.push(Token::Prime);
+        }
+
+        let n = amount.unwrap_or(1);
+        let amount = if prime { 4 - n } else { n };
+        let mv = if wide { Move::new_wide(layer, amount) } else { Move::new(layer, amount) };
+
+        (mv.unwrap(), rest)
+    }
+}
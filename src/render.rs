@@ -0,0 +1,211 @@
+//! キューブの状態を画像(SVG)にする。
+
+use std::fmt::Write;
+
+use crate::scheme::{ColorScheme, Mask, Sticker};
+use crate::{Face, State};
+
+/// SVGの設定
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// ステッカーの一辺の長さ
+    pub sticker_size: f64,
+    /// ステッカーの間の隙間
+    pub gap: f64,
+    /// 面の間の隙間。等角図では使わない。
+    pub face_gap: f64,
+    /// 最終層の図で周りに並べる側面のステッカーの幅
+    pub strip_size: f64,
+    /// ステッカーの枠線の太さ。0なら枠線を描かない。
+    pub border: f64,
+    /// 枠線の色
+    pub border_color: (u8, u8, u8),
+    /// 背景の色。Noneなら透明。
+    pub background: Option<(u8, u8, u8)>,
+    /// 配色
+    pub scheme: ColorScheme,
+    /// ステッカーごとの見せ方。隠したステッカーは描かない。
+    pub mask: Mask,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            sticker_size: 20.0,
+            gap: 1.0,
+            face_gap: 4.0,
+            strip_size: 7.0,
+            border: 1.0,
+            border_color: (0, 0, 0),
+            background: None,
+            scheme: ColorScheme::default(),
+            mask: Mask::default(),
+        }
+    }
+}
+
+// 展開図での面の位置(列, 行)。printと同じく上の下に左、前、右、後を並べ、その下に下を置く。
+const NET_LAYOUT: [(Face, f64, f64); 6] = [
+    (Face::Up, 1.0, 0.0),
+    (Face::Left, 0.0, 1.0),
+    (Face::Front, 1.0, 1.0),
+    (Face::Right, 2.0, 1.0),
+    (Face::Back, 3.0, 1.0),
+    (Face::Down, 1.0, 2.0),
+];
+
+// 面上の座標(u, v)をキューブの座標(右がx、上がy、前がz、ステッカー1枚が1)にする関数
+type FaceToCube = fn(f64, f64) -> (f64, f64, f64);
+
+// 等角図に描く面
+const ISOMETRIC_FACES: [(Face, FaceToCube); 3] = [
+    (Face::Up, |u, v| (u, 3.0, v)),
+    (Face::Front, |u, v| (u, 3.0 - v, 3.0)),
+    (Face::Right, |u, v| (3.0, 3.0 - v, 3.0 - u)),
+];
+
+fn rgb((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// SVGの書き出しを助ける。
+struct Svg<'a> {
+    body: String,
+    options: &'a SvgOptions,
+}
+
+impl<'a> Svg<'a> {
+    fn new(options: &'a SvgOptions) -> Svg<'a> {
+        Svg { body: String::new(), options }
+    }
+
+    fn stroke(&self) -> String {
+        if self.options.border > 0.0 {
+            format!(r#" stroke="{}" stroke-width="{}""#, rgb(self.options.border_color), self.options.border)
+        } else {
+            String::new()
+        }
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, sticker: Sticker) {
+        let Some(fill) = self.options.scheme.sticker_rgb(sticker).map(rgb) else { return };
+        let stroke = self.stroke();
+        writeln!(self.body, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"{}/>"#, x, y, width, height, fill, stroke).unwrap();
+    }
+
+    fn polygon(&mut self, points: &[(f64, f64)], sticker: Sticker) {
+        let Some(fill) = self.options.scheme.sticker_rgb(sticker).map(rgb) else { return };
+        let points: Vec<String> = points.iter().map(|(x, y)| format!("{:.2},{:.2}", x, y)).collect();
+        let stroke = self.stroke();
+        writeln!(self.body, r#"<polygon points="{}" fill="{}"{}/>"#, points.join(" "), fill, stroke).unwrap();
+    }
+
+    fn finish(self, width: f64, height: f64) -> String {
+        let mut svg = String::new();
+
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#, w = width, h = height).unwrap();
+
+        if let Some(bg) = self.options.background {
+            writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, rgb(bg)).unwrap();
+        }
+
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl State {
+    /// 展開図のSVG。面の並びはprintと同じ。
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let step = options.sticker_size + options.gap;
+        let face_size = 3.0 * options.sticker_size + 2.0 * options.gap;
+        let margin = options.border / 2.0;
+
+        let mut svg = Svg::new(options);
+
+        for (face, col, row) in NET_LAYOUT {
+            let x0 = margin + col * (face_size + options.face_gap);
+            let y0 = margin + row * (face_size + options.face_gap);
+
+            for (i, sticker) in self.face_stickers(face, &options.scheme, &options.mask).into_iter().enumerate() {
+                svg.rect(x0 + (i % 3) as f64 * step, y0 + (i / 3) as f64 * step, options.sticker_size, options.sticker_size, sticker);
+            }
+        }
+
+        let width = 4.0 * face_size + 3.0 * options.face_gap + options.border;
+        let height = 3.0 * face_size + 2.0 * options.face_gap + options.border;
+
+        svg.finish(width, height)
+    }
+
+    /// 上、前、右の3面を斜め上から見た等角図のSVG。
+    pub fn to_isometric_svg(&self, options: &SvgOptions) -> String {
+        let s = options.sticker_size;
+        let (cos, sin) = (s * 30_f64.to_radians().cos(), s * 30_f64.to_radians().sin());
+        let margin = options.border / 2.0;
+        let origin = (margin + 3.0 * cos, margin + 3.0 * s);
+
+        // キューブの座標を画像の座標にする。
+        let project = |x: f64, y: f64, z: f64| (origin.0 + (x - z) * cos, origin.1 + (x + z) * sin - y * s);
+
+        // 隙間の分だけ内側に縮めたステッカーの4つの角
+        let (a, b) = (options.gap / 2.0 / s, 1.0 - options.gap / 2.0 / s);
+        let corners = [(a, a), (b, a), (b, b), (a, b)];
+
+        let mut svg = Svg::new(options);
+
+        for (face, to_3d) in ISOMETRIC_FACES {
+            for (i, sticker) in self.face_stickers(face, &options.scheme, &options.mask).into_iter().enumerate() {
+                let (u, v) = ((i % 3) as f64, (i / 3) as f64);
+                let points: Vec<(f64, f64)> = corners.iter()
+                    .map(|(du, dv)| {
+                        let (x, y, z) = to_3d(u + du, v + dv);
+                        project(x, y, z)
+                    })
+                    .collect();
+
+                svg.polygon(&points, sticker);
+            }
+        }
+
+        svg.finish(6.0 * cos + options.border, 6.0 * s + options.border)
+    }
+
+    /// 上から見た最終層の図のSVG。上面の周りに前、右、後、左の一番上の列を並べる。
+    /// F2Lのステッカーは描かない。
+    pub fn to_ll_svg(&self, options: &SvgOptions) -> String {
+        let (s, t) = (options.sticker_size, options.strip_size);
+        let step = s + options.gap;
+        let face_size = 3.0 * s + 2.0 * options.gap;
+        let margin = options.border / 2.0;
+        let x0 = margin + t + options.face_gap;
+        let y0 = x0;
+        let far = x0 + face_size + options.face_gap;
+
+        let mut svg = Svg::new(options);
+
+        for (i, sticker) in self.face_stickers(Face::Up, &options.scheme, &options.mask).into_iter().enumerate() {
+            svg.rect(x0 + (i % 3) as f64 * step, y0 + (i / 3) as f64 * step, s, s, sticker);
+        }
+
+        let row = |face: Face| {
+            let stickers = self.face_stickers(face, &options.scheme, &options.mask);
+            [stickers[0], stickers[1], stickers[2]]
+        };
+
+        // 上から見て左から右、または上から下の順に並べる
+        let (front, right, back, left) = (row(Face::Front), row(Face::Right), row(Face::Back), row(Face::Left));
+
+        for i in 0..3 {
+            let d = i as f64 * step;
+            svg.rect(x0 + d, margin, s, t, back[2 - i]);
+            svg.rect(x0 + d, far, s, t, front[i]);
+            svg.rect(margin, y0 + d, t, s, left[i]);
+            svg.rect(far, y0 + d, t, s, right[2 - i]);
+        }
+
+        let size = 2.0 * (t + options.face_gap) + face_size + options.border;
+        svg.finish(size, size)
+    }
+}
@@ -0,0 +1,164 @@
+//! 配色とマスク。端末への出力と画像にするときに使う。
+
+use crate::{Color, Face, State, NUM_P};
+
+/// 配色。面ごとの色と、色ごとのRGBと文字を決める。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorScheme {
+    /// そろった状態での各面(U, F, R, D, B, Lの順)の色
+    pub faces: [Color; 6],
+    /// 各色(Colorの順)のRGB
+    pub rgb: [(u8, u8, u8); 6],
+    /// 各色(Colorの順)を表す文字
+    pub chars: [char; 6],
+    /// 灰色にしたステッカーのRGB
+    pub grey: (u8, u8, u8),
+}
+
+impl Default for ColorScheme {
+    /// 上が白、前が緑、右が赤の配色
+    fn default() -> ColorScheme {
+        ColorScheme {
+            faces: [Color::White, Color::Green, Color::Red, Color::Yellow, Color::Blue, Color::Orange],
+            rgb: [(255, 255, 255), (0, 155, 72), (183, 18, 52), (255, 213, 0), (0, 70, 173), (255, 88, 0)],
+            chars: ['W', 'G', 'R', 'Y', 'B', 'O'],
+            grey: (128, 128, 128),
+        }
+    }
+}
+
+impl ColorScheme {
+    /// 日本配色。白と青、黄と緑が向かい合う。
+    pub fn japanese() -> ColorScheme {
+        ColorScheme {
+            faces: [Color::White, Color::Green, Color::Red, Color::Blue, Color::Yellow, Color::Orange],
+            ..ColorScheme::default()
+        }
+    }
+
+    /// 色覚の違いがあっても見分けやすいRGB(Okabe-Itoの配色)
+    pub fn high_contrast() -> ColorScheme {
+        ColorScheme {
+            rgb: [(255, 255, 255), (0, 158, 115), (204, 121, 167), (240, 228, 66), (0, 114, 178), (230, 159, 0)],
+            grey: (64, 64, 64),
+            ..ColorScheme::default()
+        }
+    }
+
+    /// そろった状態での面の色
+    pub fn face_color(&self, face: Face) -> Color {
+        self.faces[face as usize]
+    }
+
+    /// ステッカーのRGB。隠したステッカーはNone。
+    pub fn sticker_rgb(&self, sticker: Sticker) -> Option<(u8, u8, u8)> {
+        match sticker {
+            Sticker::Color(c) => Some(self.rgb[c as usize]),
+            Sticker::Grey => Some(self.grey),
+            Sticker::Hidden => None,
+        }
+    }
+
+    /// ステッカーを表す文字。灰色は'-'、隠したステッカーは' '。
+    pub fn sticker_char(&self, sticker: Sticker) -> char {
+        match sticker {
+            Sticker::Color(c) => self.chars[c as usize],
+            Sticker::Grey => '-',
+            Sticker::Hidden => ' ',
+        }
+    }
+}
+
+/// マスクでステッカーをどう見せるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Show,
+    Grey,
+    Hide,
+}
+
+/// 出力するときのステッカーの見た目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sticker {
+    Color(Color),
+    Grey,
+    Hidden,
+}
+
+/// ステッカーごとの見せ方。添字はステッカーの番号(そろった状態での位置)なので、
+/// マスクはキューブを動かしてもステッカーについていく。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask([Visibility; NUM_P]);
+
+impl Default for Mask {
+    fn default() -> Mask {
+        Mask([Visibility::Show; NUM_P])
+    }
+}
+
+// 最終層のステッカー(U面と、前、右、後、左の一番上の列)
+fn is_last_layer(sticker: usize) -> bool {
+    sticker < 9 || (sticker % 9 < 3 && sticker / 9 != 3)
+}
+
+impl Mask {
+    /// ステッカーの番号から見せ方を決める。
+    pub fn from_fn(f: impl Fn(usize) -> Visibility) -> Mask {
+        let mut m = [Visibility::Show; NUM_P];
+
+        for (i, v) in m.iter_mut().enumerate() {
+            *v = f(i);
+        }
+
+        Mask(m)
+    }
+
+    /// 最終層以外を灰色にする。
+    pub fn last_layer() -> Mask {
+        Mask::from_fn(|i| if is_last_layer(i) { Visibility::Show } else { Visibility::Grey })
+    }
+
+    /// U面のステッカーだけを見せ、ほかを灰色にする。OLLの図に使う。
+    pub fn oll() -> Mask {
+        Mask::from_fn(|i| if i < 9 { Visibility::Show } else { Visibility::Grey })
+    }
+
+    /// 最終層を灰色にする。F2Lの図に使う。
+    pub fn f2l() -> Mask {
+        Mask::from_fn(|i| if is_last_layer(i) { Visibility::Grey } else { Visibility::Show })
+    }
+
+    pub fn get(&self, sticker: usize) -> Visibility {
+        self.0[sticker]
+    }
+
+    pub fn set(&mut self, sticker: usize, visibility: Visibility) {
+        self.0[sticker] = visibility;
+    }
+}
+
+impl State {
+    /// 配色を指定してキューブ面の色を取得する。
+    pub fn get_face_colors_with(&self, face: Face, scheme: &ColorScheme) -> [Color; 9] {
+        let start = face as usize * 9;
+        let p = self.get_prime().get_p();  // 位置 => ステッカー
+
+        std::array::from_fn(|i| scheme.faces[p[start + i] as usize / 9])
+    }
+
+    /// 配色とマスクを指定してキューブ面のステッカーの見た目を取得する。
+    pub fn face_stickers(&self, face: Face, scheme: &ColorScheme, mask: &Mask) -> [Sticker; 9] {
+        let start = face as usize * 9;
+        let p = self.get_prime().get_p();  // 位置 => ステッカー
+
+        std::array::from_fn(|i| {
+            let sticker = p[start + i] as usize;
+
+            match mask.get(sticker) {
+                Visibility::Show => Sticker::Color(scheme.faces[sticker / 9]),
+                Visibility::Grey => Sticker::Grey,
+                Visibility::Hide => Sticker::Hidden,
+            }
+        })
+    }
+}
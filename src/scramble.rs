@@ -0,0 +1,219 @@
+//! スクランブルを作る。
+//!
+//! 乱数はシードを指定して再現できる。
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::algorithm::Algorithm;
+use crate::coord::N_STATES;
+use crate::cubie::CubieState;
+use crate::moves::{Layer, Move};
+use crate::solvable::parity;
+use crate::solver::two_phase;
+use crate::solver::{is_move_available, SolveError, SolveOptions};
+use crate::State;
+
+/// ランダム状態スクランブルの設定
+#[derive(Debug, Clone)]
+pub struct RandomStateOptions {
+    /// これより短い手数でそろう状態は引き直す
+    pub min_length: usize,
+    /// スクランブルを作るときの探索の設定
+    pub solve: SolveOptions,
+}
+
+impl Default for RandomStateOptions {
+    fn default() -> RandomStateOptions {
+        // WCAの規則と同じく、2手未満でそろう状態は使わない
+        RandomStateOptions { min_length: 2, solve: SolveOptions::two_phase() }
+    }
+}
+
+/// ランダムな手順のスクランブルの設定
+#[derive(Debug, Clone)]
+pub struct RandomMoveOptions {
+    /// 手数
+    pub length: usize,
+    /// 回してよい層。面とスライス(M, E, S)が使える。持ち替えとワイドムーブは使わない。
+    pub layers: Vec<Layer>,
+}
+
+impl Default for RandomMoveOptions {
+    fn default() -> RandomMoveOptions {
+        RandomMoveOptions { length: 25, layers: vec![Layer::U, Layer::F, Layer::R, Layer::D, Layer::B, Layer::L] }
+    }
+}
+
+// is_move_availableで使う層の番号
+fn layer_number(layer: Layer) -> Option<usize> {
+    match layer {
+        Layer::U => Some(0),
+        Layer::F => Some(1),
+        Layer::R => Some(2),
+        Layer::D => Some(3),
+        Layer::B => Some(4),
+        Layer::L => Some(5),
+        Layer::E => Some(6),
+        Layer::S => Some(7),
+        Layer::M => Some(8),
+        Layer::X | Layer::Y | Layer::Z => None,
+    }
+}
+
+/// 一部だけをランダムにしたスクランブルの種類。ランダムにしない部分はそろったままになる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subset {
+    /// U面の層だけ(OLLとPLL)
+    LastLayer,
+    /// U面の層だけで、エッジの向きはそろっている
+    Zbll,
+    /// クロス(D面の4つのエッジ)以外
+    F2l,
+    /// コーナーだけ
+    Corners,
+    /// エッジだけ
+    Edges,
+}
+
+impl Subset {
+    // ランダムにするコーナーとエッジの位置と、エッジの向きをランダムにするか
+    fn pieces(self) -> (&'static [usize], &'static [usize], bool) {
+        const LL: [usize; 4] = [0, 1, 2, 3];
+        const CORNERS: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+        const EDGES: [usize; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        const NOT_CROSS: [usize; 8] = [0, 1, 2, 3, 8, 9, 10, 11];
+
+        match self {
+            Subset::LastLayer => (&LL, &LL, true),
+            Subset::Zbll => (&LL, &LL, false),
+            Subset::F2l => (&CORNERS, &NOT_CROSS, true),
+            Subset::Corners => (&CORNERS, &[], false),
+            Subset::Edges => (&[], &EDGES, true),
+        }
+    }
+}
+
+/// スクランブルを作る。
+pub struct Scrambler {
+    rng: StdRng,
+}
+
+impl Default for Scrambler {
+    fn default() -> Scrambler {
+        Scrambler::new()
+    }
+}
+
+impl Scrambler {
+    /// 毎回違うスクランブルを作る。
+    pub fn new() -> Scrambler {
+        Scrambler { rng: StdRng::from_entropy() }
+    }
+
+    /// シードが同じなら同じ順番で同じスクランブルを作る。
+    pub fn with_seed(seed: u64) -> Scrambler {
+        Scrambler { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// 面を回すだけで作れる状態から一様に選んだ状態。上が白、前が緑になる。
+    pub fn random_state(&mut self) -> State {
+        State::unrank(self.rng.gen_range(0..N_STATES)).unwrap()
+    }
+
+    /// ランダムな状態を2フェーズアルゴリズムでそろえ、その手順の逆をスクランブルにする。
+    pub fn random_state_scramble(&mut self, options: &RandomStateOptions) -> Result<Algorithm, SolveError> {
+        self.scramble_with(options, Scrambler::random_state)
+    }
+
+    /// subset以外がそろった状態から一様に選んだ状態。上が白、前が緑になる。
+    pub fn subset_state(&mut self, subset: Subset) -> State {
+        let (corners, edges, flip) = subset.pieces();
+        let mut cs = CubieState::new_solved();
+
+        shuffle(&mut self.rng, &mut cs.cp, corners);
+        shuffle(&mut self.rng, &mut cs.ep, edges);
+
+        // 置換の偶奇をそろえる。偶奇の合わない並べ方を1対1で合う並べ方に移すので、一様なまま。
+        if parity(&cs.cp) != parity(&cs.ep) {
+            if edges.len() >= 2 {
+                cs.ep.swap(edges[0], edges[1]);
+            } else {
+                cs.cp.swap(corners[0], corners[1]);
+            }
+        }
+
+        twist(&mut self.rng, &mut cs.co, corners, 3);
+
+        if flip {
+            twist(&mut self.rng, &mut cs.eo, edges, 2);
+        }
+
+        cs.to_state()
+    }
+
+    /// subset以外がそろったランダムな状態を2フェーズアルゴリズムでそろえ、その手順の逆をスクランブルにする。
+    pub fn subset_scramble(&mut self, subset: Subset, options: &RandomStateOptions) -> Result<Algorithm, SolveError> {
+        self.scramble_with(options, |s| s.subset_state(subset))
+    }
+
+    // 状態を引いてそろえ、min_length以上の手数になるまで繰り返す。
+    fn scramble_with(&mut self, options: &RandomStateOptions, mut draw: impl FnMut(&mut Scrambler) -> State) -> Result<Algorithm, SolveError> {
+        loop {
+            let st = draw(self);
+            let solution = Algorithm::new(two_phase::solve(&st, &options.solve)?);
+
+            if solution.count(options.solve.metric) >= options.min_length {
+                return Ok(solution.inverse());
+            }
+        }
+    }
+
+    /// ランダムな手順のスクランブル。同じ層を続けて回さず、同じ軸の層は決まった順番で回す。
+    /// 使える層がなくなった場合はそこで終わるので、指定した手数より短くなることがある(e.g. <U, D>)。
+    pub fn random_move_scramble(&mut self, options: &RandomMoveOptions) -> Algorithm {
+        let layers: Vec<(Layer, usize)> = options.layers.iter()
+            .filter_map(|l| layer_number(*l).map(|n| (*l, n)))
+            .collect();
+
+        let mut moves = vec![];
+        let mut prev = None;
+
+        for _ in 0..options.length {
+            let candidates: Vec<&(Layer, usize)> = layers.iter().filter(|(_, n)| is_move_available(prev, *n)).collect();
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            let (layer, n) = candidates[self.rng.gen_range(0..candidates.len())];
+            moves.push(Move::new(*layer, self.rng.gen_range(1..4)).unwrap());
+            prev = Some(*n);
+        }
+
+        Algorithm::new(moves)
+    }
+}
+
+// aのpositionsの位置にあるものを並べ替える。
+fn shuffle(rng: &mut StdRng, a: &mut [u8], positions: &[usize]) {
+    let mut values: Vec<u8> = positions.iter().map(|i| a[*i]).collect();
+    values.shuffle(rng);
+
+    for (i, v) in positions.iter().zip(values) {
+        a[*i] = v;
+    }
+}
+
+// oのpositionsの位置の向きをランダムにする。向きの合計がnの倍数になるように最後の1つで合わせる。
+fn twist(rng: &mut StdRng, o: &mut [u8], positions: &[usize], n: u8) {
+    if let Some((last, rest)) = positions.split_last() {
+        for i in rest {
+            o[*i] = rng.gen_range(0..n);
+        }
+
+        let sum: u8 = o.iter().sum();
+        o[*last] = (o[*last] + n - sum % n) % n;
+    }
+}
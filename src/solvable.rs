@@ -0,0 +1,132 @@
+//! キューブの状態が面を回すだけで作れるかを判定する。
+
+use std::error::Error;
+use std::fmt;
+
+use crate::cubie::CubieState;
+use crate::State;
+
+/// そろえられない状態の理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvableError {
+    /// ステッカーが違う種類のピース(センター・エッジ・コーナー)の位置にある。
+    MixedPieceType { sticker: usize },
+    /// センターの並びが持ち替えで作れない。
+    CentersInconsistent,
+    /// センターが動いている。CubieStateは持ち替えを表せない。
+    CentersMoved,
+    /// コーナーの位置にあるステッカーがコーナーとして成り立っていない。
+    InvalidCorner { position: usize },
+    /// エッジの位置にあるステッカーがエッジとして成り立っていない。
+    InvalidEdge { position: usize },
+    /// 同じピースが複数ある。
+    DuplicatePiece,
+    /// コーナーのねじれの合計が3の倍数でない。
+    CornerTwist,
+    /// エッジの反転の合計が偶数でない。
+    EdgeFlip,
+    /// コーナーとエッジの置換の偶奇が一致しない。
+    PermutationParity,
+}
+
+impl fmt::Display for SolvableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolvableError::MixedPieceType { sticker } => write!(f, "ステッカーが違う種類のピースにある: {}", sticker),
+            SolvableError::CentersInconsistent => write!(f, "センターの並びが不正"),
+            SolvableError::CentersMoved => write!(f, "センターが動いている"),
+            SolvableError::InvalidCorner { position } => write!(f, "不正なコーナー: {}", position),
+            SolvableError::InvalidEdge { position } => write!(f, "不正なエッジ: {}", position),
+            SolvableError::DuplicatePiece => write!(f, "同じピースが複数ある"),
+            SolvableError::CornerTwist => write!(f, "コーナーのねじれの合計が3の倍数でない"),
+            SolvableError::EdgeFlip => write!(f, "エッジの反転の合計が奇数"),
+            SolvableError::PermutationParity => write!(f, "コーナーとエッジの置換の偶奇が一致しない"),
+        }
+    }
+}
+
+impl Error for SolvableError {}
+
+// ステッカーの種類。0: センター、1: エッジ、2: コーナー
+fn piece_type(sticker: usize) -> usize {
+    match sticker % 9 {
+        4 => 0,
+        1 | 3 | 5 | 7 => 1,
+        _ => 2,
+    }
+}
+
+// 置換の偶奇。偶置換なら0を返す。
+pub(crate) fn parity(p: &[u8]) -> usize {
+    let mut count = 0;
+
+    for i in 0..p.len() {
+        for j in i+1..p.len() {
+            if p[i] > p[j] {
+                count += 1;
+            }
+        }
+    }
+
+    count % 2
+}
+
+impl CubieState {
+    /// 面を回すだけで作れる状態であればOk(())を返す。
+    pub fn check_solvable(&self) -> Result<(), SolvableError> {
+        let mut cp = self.cp;
+        cp.sort();
+        let mut ep = self.ep;
+        ep.sort();
+
+        if cp != CubieState::new_solved().cp || ep != CubieState::new_solved().ep {
+            return Err(SolvableError::DuplicatePiece);
+        }
+
+        if self.co.iter().any(|v| *v >= 3) || self.co.iter().map(|v| *v as usize).sum::<usize>() % 3 != 0 {
+            return Err(SolvableError::CornerTwist);
+        }
+
+        if self.eo.iter().any(|v| *v >= 2) || self.eo.iter().map(|v| *v as usize).sum::<usize>() % 2 != 0 {
+            return Err(SolvableError::EdgeFlip);
+        }
+
+        if parity(&self.cp) != parity(&self.ep) {
+            return Err(SolvableError::PermutationParity);
+        }
+
+        Ok(())
+    }
+}
+
+impl State {
+    /// 面を回すだけで作れる状態であればOk(())を返す。
+    /// 持ち替えでセンターが動いていてもよい。
+    pub fn check_solvable(&self) -> Result<(), SolvableError> {
+        self.cubie_state().map(|_| ())
+    }
+
+    // センターが元の位置に来るように持ち替えてCubieStateにする。面を回すだけで作れない状態ならエラーを返す。
+    pub(crate) fn cubie_state(&self) -> Result<CubieState, SolvableError> {
+        let p = self.get_p();
+
+        for (sticker, pos) in p.iter().enumerate() {
+            if piece_type(sticker) != piece_type(*pos as usize) {
+                return Err(SolvableError::MixedPieceType { sticker });
+            }
+        }
+
+        // センターが元の位置に来るように持ち替える
+        self.orientation().ok_or(SolvableError::CentersInconsistent)?;
+
+        let cs = CubieState::from_state(&self.canonical())?;
+        cs.check_solvable()?;
+
+        Ok(cs)
+    }
+
+    /// 面を回すだけで作れる状態であればtrueを返す。
+    pub fn is_solvable(&self) -> bool {
+        self.check_solvable().is_ok()
+    }
+}
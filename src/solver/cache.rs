@@ -0,0 +1,174 @@
+//! 作るのに時間がかかる枝刈り表をファイルに保存して再利用する。
+//!
+//! ファイルは32バイトのヘッダと表の内容からなる。ヘッダはリトルエンディアンで
+//! - マジックナンバー "CUBETBL\0" (8バイト)
+//! - フォーマットのバージョン (u32)
+//! - 表の種類 (u32)
+//! - 表のパラメータ (u32)
+//! - 予約 (u32)
+//! - 表の内容のFNV-1aハッシュ (u64)
+//!
+//! 読み込むときはヘッダと長さとハッシュを確認してからメモリマップする。
+//! ファイルがない場合や壊れている場合は表を作り直して保存する。
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use memmap2::Mmap;
+
+const MAGIC: &[u8; 8] = b"CUBETBL\0";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 32;
+
+/// 保存する表の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TableKind {
+    TwistSlice = 1,
+    FlipSlice = 2,
+    CornersSlice = 3,
+    UdEdgesSlice = 4,
+    CornersPattern = 5,
+    EdgesPattern = 6,
+}
+
+impl TableKind {
+    fn file_name(self, param: u32) -> String {
+        let name = match self {
+            TableKind::TwistSlice => "twist_slice",
+            TableKind::FlipSlice => "flip_slice",
+            TableKind::CornersSlice => "corners_slice",
+            TableKind::UdEdgesSlice => "ud_edges_slice",
+            TableKind::CornersPattern => "corners_pattern",
+            TableKind::EdgesPattern => "edges_pattern",
+        };
+
+        format!("{}_{}.tbl", name, param)
+    }
+}
+
+/// メモリ上に作った表か、ファイルをメモリマップした表
+pub(crate) enum Table {
+    Owned(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Deref for Table {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Table::Owned(v) => v,
+            Table::Mapped(m) => &m[HEADER_LEN..],
+        }
+    }
+}
+
+// FNV-1a
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+fn header(kind: TableKind, param: u32, data: &[u8]) -> [u8; HEADER_LEN] {
+    let mut h = [0_u8; HEADER_LEN];
+
+    h[0..8].copy_from_slice(MAGIC);
+    h[8..12].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    h[12..16].copy_from_slice(&(kind as u32).to_le_bytes());
+    h[16..20].copy_from_slice(&param.to_le_bytes());
+    h[24..32].copy_from_slice(&checksum(data).to_le_bytes());
+
+    h
+}
+
+/// 表を保存するディレクトリ
+#[derive(Debug, Clone)]
+pub struct TableCache {
+    dir: PathBuf,
+}
+
+impl TableCache {
+    /// 表をdirに保存する。ディレクトリがなければ保存するときに作る。
+    pub fn new(dir: impl Into<PathBuf>) -> TableCache {
+        TableCache { dir: dir.into() }
+    }
+
+    /// 表を保存するディレクトリ
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 保存された表を読み込む。なかったり壊れていたりしたらbuildで作って保存する。
+    /// 保存に失敗しても作った表を返す。
+    pub(crate) fn load_or_build(&self, kind: TableKind, param: u32, len: usize,
+                                build: impl FnOnce() -> Vec<u8>) -> Table {
+        let path = self.dir.join(kind.file_name(param));
+
+        if let Some(mmap) = load(&path, kind, param, len) {
+            return Table::Mapped(mmap);
+        }
+
+        let data = build();
+        let _ = save(&self.dir, &path, &header(kind, param, &data), &data);
+
+        Table::Owned(data)
+    }
+}
+
+// ヘッダと長さとハッシュが正しければメモリマップして返す。
+fn load(path: &Path, kind: TableKind, param: u32, len: usize) -> Option<Mmap> {
+    let file = File::open(path).ok()?;
+
+    // SAFETY: ファイルはこのモジュールが一時ファイルから置き換えて作るだけで、書き換えない。
+    let mmap = unsafe { Mmap::map(&file) }.ok()?;
+
+    if mmap.len() != HEADER_LEN + len {
+        return None;
+    }
+
+    let data = &mmap[HEADER_LEN..];
+
+    if mmap[0..HEADER_LEN] != header(kind, param, data) {
+        return None;
+    }
+
+    Some(mmap)
+}
+
+// 一時ファイルの名前が重ならないようにする連番
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// 一時ファイルに書いてから置き換えるので、途中で失敗しても壊れたファイルは残らない。
+// 一時ファイルの名前にはプロセスIDと連番をつけ、同じ表を同時に保存しても互いに壊さない。
+fn save(dir: &Path, path: &Path, header: &[u8], data: &[u8]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_extension(format!("{}.{}.tmp", process::id(), n));
+
+    let result = File::options().write(true).create_new(true).open(&tmp).and_then(|mut file| {
+        file.write_all(header)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+
+        fs::rename(&tmp, path)
+    });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// cacheがあれば保存された表を使い、なければbuildで作る。
+pub(crate) fn load_or_build(cache: Option<&TableCache>, kind: TableKind, param: u32, len: usize,
+                            build: impl FnOnce() -> Vec<u8>) -> Table {
+    match cache {
+        Some(cache) => cache.load_or_build(kind, param, len, build),
+        None => Table::Owned(build()),
+    }
+}
@@ -0,0 +1,133 @@
+//! キューブを解くための探索
+
+pub mod two_phase;
+pub mod optimal;
+pub mod cache;
+mod tables;
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::algorithm::Metric;
+use crate::cubie::{CubieState, CENTER_FACELETS};
+use crate::moves::{self, Layer, Move};
+use crate::solvable::SolvableError;
+use crate::State;
+
+/// 面を回す18種類の動き。面の順番はU, F, R, D, B, L。
+pub(crate) const FACE_MOVES: [Move; 18] = [
+    Move::face(Layer::U, 1), Move::face(Layer::U, 2), Move::face(Layer::U, 3),
+    Move::face(Layer::F, 1), Move::face(Layer::F, 2), Move::face(Layer::F, 3),
+    Move::face(Layer::R, 1), Move::face(Layer::R, 2), Move::face(Layer::R, 3),
+    Move::face(Layer::D, 1), Move::face(Layer::D, 2), Move::face(Layer::D, 3),
+    Move::face(Layer::B, 1), Move::face(Layer::B, 2), Move::face(Layer::B, 3),
+    Move::face(Layer::L, 1), Move::face(Layer::L, 2), Move::face(Layer::L, 3),
+];
+
+/// U, D, R2, L2, F2, B2 (FACE_MOVESの添字)
+pub(crate) const PHASE2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+/// 前の1手を考慮して次の1手として使える操作であるかを判定する。
+/// 引数はFACE_MOVESの添字を3で割った面の番号。スライスはE, S, Mの順に6, 7, 8とする。
+/// 番号を3で割った余りが同じなら同じ軸の層になる。
+/// - 同じ面は連続して回さない (e.g. R' R2 は不可)
+/// - 同じ軸の層を回すときは順序を固定する (e.g. U Dは良いが、D Uは不可)
+pub(crate) fn is_move_available(prev_face: Option<usize>, cur_face: usize) -> bool {
+    match prev_face {
+        None => true,  // 最初の一手はどの操作も可能
+        Some(prev) => prev != cur_face && !(prev % 3 == cur_face % 3 && prev > cur_face),
+    }
+}
+
+/// FACE_MOVESの添字の動きの手数。探索はHTMかQTMで数える。
+pub(crate) fn move_cost(m: usize, metric: Metric) -> usize {
+    metric.cost(FACE_MOVES[m])
+}
+
+/// 面を回す動きだけで探索するので、スライスや持ち替えを1手と数える数え方では最短にならない。
+pub(crate) fn check_metric(metric: Metric) -> Result<(), SolveError> {
+    match metric {
+        Metric::Htm | Metric::Qtm => Ok(()),
+        _ => Err(SolveError::UnsupportedMetric(metric)),
+    }
+}
+
+/// 探索の設定。標準の最大手数は探索の方法で違うので、two_phaseかoptimalから作る。
+#[derive(Debug, Clone)]
+pub struct SolveOptions {
+    /// 解の最大手数(metricで数える)
+    pub max_length: usize,
+    /// 探索を打ち切る時間
+    pub timeout: Option<Duration>,
+    /// 手数の数え方。HtmかQtmだけ使える。
+    pub metric: Metric,
+}
+
+impl SolveOptions {
+    /// 2フェーズの探索の標準の設定。最大手数は21。
+    pub fn two_phase() -> SolveOptions {
+        SolveOptions { max_length: 21, timeout: None, metric: Metric::Htm }
+    }
+
+    /// 最短の解の探索の標準の設定。最大手数は20。
+    pub fn optimal() -> SolveOptions {
+        SolveOptions { max_length: 20, timeout: None, metric: Metric::Htm }
+    }
+}
+
+/// 解が見つからなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    /// 面を回すだけではそろえられない状態。
+    Unsolvable(SolvableError),
+    /// 指定した手数以内の解がない。
+    NotFound,
+    /// 制限時間内に解が見つからなかった。
+    Timeout,
+    /// 探索できない手数の数え方が指定された。
+    UnsupportedMetric(Metric),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Unsolvable(e) => write!(f, "そろえられない状態: {}", e),
+            SolveError::NotFound => write!(f, "解が見つからない"),
+            SolveError::Timeout => write!(f, "時間切れ"),
+            SolveError::UnsupportedMetric(m) => write!(f, "対応していない手数の数え方: {:?}", m),
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Unsolvable(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// 探索する状態を作る。
+/// センターが元の位置に来るように持ち替えたCubieStateと、その持ち替えを返す。
+pub(crate) fn normalize(st: &State) -> Result<(CubieState, &'static State), SolveError> {
+    st.check_solvable().map_err(SolveError::Unsolvable)?;
+
+    let p = st.get_p();
+    let rot = moves::rotations().iter()
+        .find(|r| CENTER_FACELETS.iter().all(|c| r.get_p()[*c as usize] == p[*c as usize]))
+        .unwrap();
+
+    Ok((CubieState::from_state(&st.apply(&rot.get_prime())).unwrap(), rot))
+}
+
+/// normalizeしたキューブの解(FACE_MOVESの添字)を持ち替える前の向きでの動きに直す。
+pub(crate) fn denormalize(solution: &[usize], rot: &State) -> Vec<Move> {
+    let rot_prime = rot.get_prime();
+
+    solution.iter().map(|m| {
+        let mv = rot_prime.apply(&State::get_move(FACE_MOVES[*m])).apply(rot);
+        *FACE_MOVES.iter().find(|f| State::get_move(**f) == mv).unwrap()
+    }).collect()
+}
@@ -0,0 +1,376 @@
+//! パターンデータベースを使ったIDA*で最短の解を求める (Korfの方法)
+//!
+//! コーナーの置換と向きのデータベースと、エッジを2組に分けてそれぞれの位置と向きを記録した
+//! データベースを作り、それらの最大値を残り手数の下界として探索する。
+//! どのデータベースの値も実際の手数を超えないので、最初に見つかった解が最短になる。
+
+use std::time::Instant;
+
+use crate::algorithm::Metric;
+use crate::coord::{N_CORNERS, N_TWIST};
+use crate::cubie::NUM_EDGES;
+use crate::moves::Move;
+use crate::solver::cache::{load_or_build, Table, TableCache, TableKind};
+use crate::solver::tables::{face_move_cubies, move_tables, MoveTables, N_MOVES};
+use crate::solver::{check_metric, denormalize, is_move_available, move_cost, normalize, SolveError, SolveOptions};
+use crate::State;
+
+/// 作成するパターンデータベースの設定
+#[derive(Debug, Clone)]
+pub struct DatabaseOptions {
+    /// コーナーのデータベース(8! * 3^7 要素)を使うか
+    pub corners: bool,
+    /// エッジのデータベース1つで記録するエッジの数 (0..=6)。0ならエッジのデータベースを使わない。
+    /// UR, UF, ...の先頭からと末尾からの2つのデータベースを作る。
+    pub edges: usize,
+}
+
+impl Default for DatabaseOptions {
+    fn default() -> DatabaseOptions {
+        DatabaseOptions { corners: true, edges: 6 }
+    }
+}
+
+// 4ビットずつ記録した表の値
+fn nibble(data: &[u8], idx: usize) -> u8 {
+    (data[idx / 2] >> (idx % 2 * 4)) & 0xF
+}
+
+fn set_nibble(data: &mut [u8], idx: usize, v: u8) {
+    let shift = idx % 2 * 4;
+    data[idx / 2] = (data[idx / 2] & !(0xF << shift)) | (v << shift);
+}
+
+/// 状態からそろった状態までの手数を4ビットずつ記録する表
+pub(crate) struct PatternDatabase {
+    data: Table,
+}
+
+impl PatternDatabase {
+    const EMPTY: u8 = 0xF;
+
+    fn get(&self, idx: usize) -> u8 {
+        nibble(&self.data, idx)
+    }
+
+    // startから幅優先探索で作る。nextは添字から各動きで移る先の添字を求める。
+    // 半分以上埋まったら、空いている要素から逆向きに探索したほうが速い。
+    fn build(n: usize, start: usize, next: impl Fn(usize, &mut [usize; N_MOVES])) -> Vec<u8> {
+        let mut data = vec![0xFF; n.div_ceil(2)];
+        set_nibble(&mut data, start, 0);
+
+        let mut count = 1;
+        let mut depth = 0;
+        let mut neighbors = [0; N_MOVES];
+
+        while count < n {
+            let backward = count > n / 2;
+            let prev_count = count;
+
+            for idx in 0..n {
+                let v = nibble(&data, idx);
+
+                if backward {
+                    if v != Self::EMPTY {
+                        continue;
+                    }
+
+                    next(idx, &mut neighbors);
+
+                    if neighbors.iter().any(|nb| nibble(&data, *nb) == depth) {
+                        set_nibble(&mut data, idx, depth + 1);
+                        count += 1;
+                    }
+                } else {
+                    if v != depth {
+                        continue;
+                    }
+
+                    next(idx, &mut neighbors);
+
+                    for nb in neighbors {
+                        if nibble(&data, nb) == Self::EMPTY {
+                            set_nibble(&mut data, nb, depth + 1);
+                            count += 1;
+                        }
+                    }
+                }
+            }
+
+            if count == prev_count {
+                break;  // 残りは到達できない
+            }
+
+            depth += 1;
+        }
+
+        data
+    }
+}
+
+// エッジのデータベースで記録するエッジの最大数
+const MAX_EDGES: usize = 6;
+
+// いくつかのエッジの位置と向きのデータベース
+struct EdgeDatabase {
+    edges: Vec<u8>,
+    db: PatternDatabase,
+}
+
+impl EdgeDatabase {
+    // 各エッジの(位置, 向き)から添字を求める。
+    fn encode(pieces: impl Iterator<Item = (u8, u8)>) -> usize {
+        let mut perm = 0;
+        let mut ori = 0;
+        let mut used = 0_u16;
+        let mut k = 0;
+
+        for (j, (pos, o)) in pieces.enumerate() {
+            let d = pos as usize - (used & ((1 << pos) - 1)).count_ones() as usize;
+            perm = perm * (NUM_EDGES - j) + d;
+            ori = ori * 2 + o as usize;
+            used |= 1 << pos;
+            k += 1;
+        }
+
+        (perm << k) | ori
+    }
+
+    // encodeの逆
+    fn decode(k: usize, idx: usize) -> [(u8, u8); MAX_EDGES] {
+        let mut ori = idx & ((1 << k) - 1);
+        let mut perm = idx >> k;
+        let mut pieces = [(0, 0); MAX_EDGES];
+
+        for j in (0..k).rev() {
+            pieces[j].1 = (ori % 2) as u8;
+            ori /= 2;
+            pieces[j].0 = (perm % (NUM_EDGES - j)) as u8;
+            perm /= NUM_EDGES - j;
+        }
+
+        let mut used = 0_u16;
+
+        for piece in pieces[0..k].iter_mut() {
+            // 使われていない位置のうちpiece.0番目
+            let mut d = piece.0;
+            let mut pos = 0;
+
+            loop {
+                if used & (1 << pos) == 0 {
+                    if d == 0 {
+                        break;
+                    }
+
+                    d -= 1;
+                }
+
+                pos += 1;
+            }
+
+            piece.0 = pos;
+            used |= 1 << pos;
+        }
+
+        pieces
+    }
+
+    fn new(edges: Vec<u8>, dest: &[[(u8, u8); NUM_EDGES]; N_MOVES], cache: Option<&TableCache>) -> EdgeDatabase {
+        let k = edges.len();
+        let n = (NUM_EDGES - k + 1..=NUM_EDGES).product::<usize>() << k;
+        let start = Self::encode(edges.iter().map(|e| (*e, 0)));
+        let param = edges.iter().fold(0, |bits, e| bits | 1 << e);
+
+        let data = load_or_build(cache, TableKind::EdgesPattern, param, n.div_ceil(2), || {
+            PatternDatabase::build(n, start, |idx, neighbors| {
+                let pieces = Self::decode(k, idx);
+
+                for (m, nb) in neighbors.iter_mut().enumerate() {
+                    *nb = Self::encode(pieces[0..k].iter().map(|(pos, o)| {
+                        let (pos2, o2) = dest[m][*pos as usize];
+                        (pos2, o ^ o2)
+                    }));
+                }
+            })
+        });
+
+        EdgeDatabase { edges, db: PatternDatabase { data } }
+    }
+
+    fn get(&self, pos: &[u8; NUM_EDGES], ori: &[u8; NUM_EDGES]) -> u8 {
+        let idx = Self::encode(self.edges.iter().map(|e| (pos[*e as usize], ori[*e as usize])));
+        self.db.get(idx)
+    }
+}
+
+/// パターンデータベースを持ち、最短の解を求める。
+pub struct OptimalSolver {
+    corners: Option<PatternDatabase>,
+    edges: Vec<EdgeDatabase>,
+    dest: [[(u8, u8); NUM_EDGES]; N_MOVES],  // [動き][エッジの位置] => (移動先, 向きの変化)
+}
+
+impl Default for OptimalSolver {
+    fn default() -> OptimalSolver {
+        OptimalSolver::new(&DatabaseOptions::default())
+    }
+}
+
+impl OptimalSolver {
+    /// パターンデータベースを作る。標準の設定では時間がかかる。
+    pub fn new(options: &DatabaseOptions) -> OptimalSolver {
+        OptimalSolver::build(options, None)
+    }
+
+    /// パターンデータベースをcacheから読み込む。なければ作って保存する。
+    pub fn with_cache(options: &DatabaseOptions, cache: &TableCache) -> OptimalSolver {
+        OptimalSolver::build(options, Some(cache))
+    }
+
+    fn build(options: &DatabaseOptions, cache: Option<&TableCache>) -> OptimalSolver {
+        assert!(options.edges <= MAX_EDGES, "エッジの数は{}以下", MAX_EDGES);
+
+        let mt = move_tables();
+
+        let mut dest = [[(0, 0); NUM_EDGES]; N_MOVES];
+
+        for (m, mv) in face_move_cubies().iter().enumerate() {
+            for i in 0..NUM_EDGES {
+                dest[m][mv.ep[i] as usize] = (i as u8, mv.eo[i]);
+            }
+        }
+
+        let corners = options.corners.then(|| {
+            let n = N_CORNERS * N_TWIST;
+
+            let data = load_or_build(cache, TableKind::CornersPattern, 0, n.div_ceil(2), || {
+                PatternDatabase::build(n, 0, |idx, neighbors| {
+                    let (c, t) = (idx / N_TWIST, idx % N_TWIST);
+
+                    for (m, nb) in neighbors.iter_mut().enumerate() {
+                        *nb = mt.corners_move[c][m] as usize * N_TWIST + mt.twist_move[t][m] as usize;
+                    }
+                })
+            });
+
+            PatternDatabase { data }
+        });
+
+        let mut edges = vec![];
+
+        if options.edges > 0 {
+            let k = options.edges as u8;
+            edges.push(EdgeDatabase::new((0..k).collect(), &dest, cache));
+            edges.push(EdgeDatabase::new((NUM_EDGES as u8 - k..NUM_EDGES as u8).collect(), &dest, cache));
+        }
+
+        OptimalSolver { corners, edges, dest }
+    }
+
+    /// 最短の解を求める。解の手数がそろった状態までの最短距離(options.metricで数える)になる。
+    /// 距離は`Algorithm::new(solution).count(options.metric)`で数える。
+    /// 持ち替えでセンターが動いている場合は、その向きのまま回す手順を返す。
+    pub fn solve(&self, st: &State, options: &SolveOptions) -> Result<Vec<Move>, SolveError> {
+        check_metric(options.metric)?;
+
+        let (cube, rot) = normalize(st)?;
+
+        let mut pos = [0_u8; NUM_EDGES];
+        let mut ori = [0_u8; NUM_EDGES];
+
+        for i in 0..NUM_EDGES {
+            pos[cube.ep[i] as usize] = i as u8;
+            ori[cube.ep[i] as usize] = cube.eo[i];
+        }
+
+        let mut search = Search {
+            solver: self,
+            mt: move_tables(),
+            solution: vec![],
+            metric: options.metric,
+            nodes: 0,
+            deadline: options.timeout.map(|t| Instant::now() + t),
+        };
+
+        let corners = cube.corners();
+        let twist = cube.twist();
+
+        for depth in search.h(corners, twist, &pos, &ori)..=options.max_length {
+            if search.dfs(corners, twist, &pos, &ori, depth)? {
+                return Ok(denormalize(&search.solution, rot));
+            }
+        }
+
+        Err(SolveError::NotFound)
+    }
+}
+
+struct Search<'a> {
+    solver: &'a OptimalSolver,
+    mt: &'a MoveTables,
+    solution: Vec<usize>,  // FACE_MOVESの添字
+    metric: Metric,
+    nodes: u64,
+    deadline: Option<Instant>,
+}
+
+impl Search<'_> {
+    // 残り手数の下界
+    fn h(&self, corners: usize, twist: usize, pos: &[u8; NUM_EDGES], ori: &[u8; NUM_EDGES]) -> usize {
+        let mut h = self.solver.corners.as_ref().map_or(0, |db| db.get(corners * N_TWIST + twist));
+
+        for db in &self.solver.edges {
+            h = h.max(db.get(pos, ori));
+        }
+
+        h as usize
+    }
+
+    fn dfs(&mut self, corners: usize, twist: usize, pos: &[u8; NUM_EDGES], ori: &[u8; NUM_EDGES],
+           depth: usize) -> Result<bool, SolveError> {
+        if depth == 0 {
+            return Ok(corners == 0 && twist == 0 &&
+                pos.iter().enumerate().all(|(e, p)| e == *p as usize) && ori.iter().all(|o| *o == 0));
+        }
+
+        self.nodes += 1;
+
+        if self.nodes.is_multiple_of(0x10000) && self.deadline.is_some_and(|d| Instant::now() > d) {
+            return Err(SolveError::Timeout);
+        }
+
+        for m in 0..N_MOVES {
+            if !is_move_available(self.solution.last().map(|m| m / 3), m / 3) {
+                continue;
+            }
+
+            let corners2 = self.mt.corners_move[corners][m] as usize;
+            let twist2 = self.mt.twist_move[twist][m] as usize;
+            let mut pos2 = *pos;
+            let mut ori2 = *ori;
+
+            for e in 0..NUM_EDGES {
+                let (p, o) = self.solver.dest[m][pos[e] as usize];
+                pos2[e] = p;
+                ori2[e] ^= o;
+            }
+
+            // データベースはHTMの手数なので、QTMでも下界になる
+            let c = move_cost(m, self.metric);
+
+            if self.h(corners2, twist2, &pos2, &ori2) + c > depth {
+                continue;
+            }
+
+            self.solution.push(m);
+
+            if self.dfs(corners2, twist2, &pos2, &ori2, depth - c)? {
+                return Ok(true);
+            }
+
+            self.solution.pop();
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,64 @@
+//! 探索で共有する座標の移動表
+
+use std::sync::OnceLock;
+
+use crate::coord::{N_CORNERS, N_FLIP, N_SLICE_SORTED, N_TWIST, N_UD_EDGES};
+use crate::cubie::CubieState;
+use crate::solver::{FACE_MOVES, PHASE2_MOVES};
+use crate::State;
+
+pub(crate) const N_MOVES: usize = 18;
+
+/// 座標の移動表。添字は[座標][FACE_MOVESの添字]。
+pub(crate) struct MoveTables {
+    pub moves: Vec<CubieState>,
+    pub twist_move: Vec<[u16; N_MOVES]>,
+    pub flip_move: Vec<[u16; N_MOVES]>,
+    pub slice_sorted_move: Vec<[u16; N_MOVES]>,
+    pub corners_move: Vec<[u16; N_MOVES]>,
+    pub ud_edges_move: Vec<[u16; N_MOVES]>,  // フェーズ2の動きだけ
+}
+
+static MOVE_TABLES: OnceLock<MoveTables> = OnceLock::new();
+
+// 座標を設定したキューブに各動きを適用した座標の表を作る。
+fn move_table(n: usize, mvs: &[usize], moves: &[CubieState],
+              set: fn(&mut CubieState, usize), get: fn(&CubieState) -> usize) -> Vec<[u16; N_MOVES]> {
+    let mut table = vec![[0_u16; N_MOVES]; n];
+    let mut cs = CubieState::new_solved();
+
+    for (i, row) in table.iter_mut().enumerate() {
+        set(&mut cs, i);
+
+        for m in mvs {
+            row[*m] = get(&cs.apply(&moves[*m])) as u16;
+        }
+    }
+
+    table
+}
+
+/// 面を回す18種類の動きのCubieState
+pub(crate) fn face_move_cubies() -> Vec<CubieState> {
+    FACE_MOVES.iter()
+        .map(|m| CubieState::from_state(&State::get_move(*m)).unwrap())
+        .collect()
+}
+
+pub(crate) fn move_tables() -> &'static MoveTables {
+    MOVE_TABLES.get_or_init(|| {
+        let all: Vec<usize> = (0..N_MOVES).collect();
+        let moves = face_move_cubies();
+
+        MoveTables {
+            twist_move: move_table(N_TWIST, &all, &moves, CubieState::set_twist, CubieState::twist),
+            flip_move: move_table(N_FLIP, &all, &moves, CubieState::set_flip, CubieState::flip),
+            slice_sorted_move: move_table(N_SLICE_SORTED, &all, &moves,
+                                          CubieState::set_slice_sorted, CubieState::slice_sorted),
+            corners_move: move_table(N_CORNERS, &all, &moves, CubieState::set_corners, CubieState::corners),
+            ud_edges_move: move_table(N_UD_EDGES, &PHASE2_MOVES, &moves,
+                                      CubieState::set_ud_edges, CubieState::ud_edges),
+            moves,
+        }
+    })
+}
@@ -0,0 +1,331 @@
+//! Kociembaの2フェーズアルゴリズム
+//!
+//! フェーズ1でコーナーとエッジの向きをそろえ、スライスのエッジをスライスに集める。
+//! フェーズ2ではU, D, R2, L2, F2, B2だけを使ってそろえる。
+//! どちらのフェーズも座標の移動表と枝刈り表を使ったIDA*で探索する。
+//! フェーズ2の手数は[`MAX_PHASE2_LENGTH`]までに制限し、見つからなければ制限なしで探索し直す。
+//! 持ち替えた状態と逆の状態も同時に探索し、フェーズ1が短く済む向きの解を使う。
+
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::algorithm::{Algorithm, Metric};
+use crate::coord::{N_CORNERS, N_FLIP, N_SLICE, N_SLICE_PERM, N_TWIST, N_UD_EDGES};
+use crate::cubie::CubieState;
+use crate::moves::{Layer, Move, Rotation};
+use crate::solver::cache::{load_or_build, Table, TableCache, TableKind};
+use crate::solver::tables::{move_tables, MoveTables, N_MOVES};
+use crate::solver::{check_metric, denormalize, is_move_available, move_cost, normalize, SolveError, SolveOptions, FACE_MOVES, PHASE2_MOVES};
+use crate::State;
+
+/// フェーズ2の最大手数(HTM)。QTMではこの2倍にする。
+/// フェーズ1が短いときにフェーズ2を深く探索すると時間がかかりすぎる。
+pub const MAX_PHASE2_LENGTH: usize = 12;
+
+// 枝刈り表
+struct Tables {
+    twist_slice_prune: Table,
+    flip_slice_prune: Table,
+    corners_slice_prune: Table,
+    ud_edges_slice_prune: Table,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+// 2つの座標の組からそろった状態までの手数の表を幅優先探索で作る。
+fn prune_table(n1: usize, n2: usize, mvs: &[usize], next: impl Fn(usize, usize, usize) -> (usize, usize)) -> Vec<u8> {
+    const EMPTY: u8 = u8::MAX;
+
+    let mut table = vec![EMPTY; n1 * n2];
+    table[0] = 0;
+
+    let mut count = 1;
+    let mut depth = 0;
+
+    while count < n1 * n2 {
+        for idx in 0..n1 * n2 {
+            if table[idx] != depth {
+                continue;
+            }
+
+            for m in mvs {
+                let (c1, c2) = next(idx / n2, idx % n2, *m);
+                let idx2 = c1 * n2 + c2;
+
+                if table[idx2] == EMPTY {
+                    table[idx2] = depth + 1;
+                    count += 1;
+                }
+            }
+        }
+
+        depth += 1;
+    }
+
+    table
+}
+
+fn build_tables(cache: Option<&TableCache>) -> Tables {
+    let all: Vec<usize> = (0..N_MOVES).collect();
+    let mt = move_tables();
+
+    let slice_move = |s: usize, m: usize| mt.slice_sorted_move[s * N_SLICE_PERM][m] as usize / N_SLICE_PERM;
+
+    Tables {
+        twist_slice_prune: load_or_build(cache, TableKind::TwistSlice, 0, N_TWIST * N_SLICE, || {
+            prune_table(N_TWIST, N_SLICE, &all, |t, s, m| (mt.twist_move[t][m] as usize, slice_move(s, m)))
+        }),
+        flip_slice_prune: load_or_build(cache, TableKind::FlipSlice, 0, N_FLIP * N_SLICE, || {
+            prune_table(N_FLIP, N_SLICE, &all, |f, s, m| (mt.flip_move[f][m] as usize, slice_move(s, m)))
+        }),
+        corners_slice_prune: load_or_build(cache, TableKind::CornersSlice, 0, N_CORNERS * N_SLICE_PERM, || {
+            prune_table(N_CORNERS, N_SLICE_PERM, &PHASE2_MOVES,
+                |c, s, m| (mt.corners_move[c][m] as usize, mt.slice_sorted_move[s][m] as usize))
+        }),
+        ud_edges_slice_prune: load_or_build(cache, TableKind::UdEdgesSlice, 0, N_UD_EDGES * N_SLICE_PERM, || {
+            prune_table(N_UD_EDGES, N_SLICE_PERM, &PHASE2_MOVES,
+                |e, s, m| (mt.ud_edges_move[e][m] as usize, mt.slice_sorted_move[s][m] as usize))
+        }),
+    }
+}
+
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(|| build_tables(None))
+}
+
+struct Search<'a> {
+    mt: &'a MoveTables,
+    t: &'a Tables,
+    cube: CubieState,
+    solution: Vec<usize>,  // FACE_MOVESの添字
+    cost: usize,  // solutionの手数
+    max_length: usize,
+    max_phase2: usize,  // フェーズ2の最大手数(metricで数える)
+    metric: Metric,
+    deadline: Option<Instant>,
+}
+
+impl Search<'_> {
+    fn last_face(&self) -> Option<usize> {
+        self.solution.last().map(|m| m / 3)
+    }
+
+    fn push(&mut self, m: usize) {
+        self.solution.push(m);
+        self.cost += move_cost(m, self.metric);
+    }
+
+    fn pop(&mut self) {
+        let m = self.solution.pop().unwrap();
+        self.cost -= move_cost(m, self.metric);
+    }
+
+    fn start_phase1(&mut self, depth: usize) -> Result<bool, SolveError> {
+        let twist = self.cube.twist();
+        let flip = self.cube.flip();
+        let slice = self.cube.slice_sorted() / N_SLICE_PERM;
+
+        self.phase1(twist, flip, slice, depth)
+    }
+
+    fn phase1(&mut self, twist: usize, flip: usize, slice: usize, depth: usize) -> Result<bool, SolveError> {
+        if depth == 0 {
+            // 最後の手がフェーズ2の動きなら、より短いフェーズ1の解で探索済み
+            if twist == 0 && flip == 0 && slice == 0 &&
+                self.solution.last().is_none_or(|m| !PHASE2_MOVES.contains(m)) {
+                return self.start_phase2();
+            }
+
+            return Ok(false);
+        }
+
+        for m in 0..N_MOVES {
+            if !is_move_available(self.last_face(), m / 3) {
+                continue;
+            }
+
+            let twist2 = self.mt.twist_move[twist][m] as usize;
+            let flip2 = self.mt.flip_move[flip][m] as usize;
+            let slice2 = self.mt.slice_sorted_move[slice * N_SLICE_PERM][m] as usize / N_SLICE_PERM;
+
+            let h = self.t.twist_slice_prune[twist2 * N_SLICE + slice2]
+                .max(self.t.flip_slice_prune[flip2 * N_SLICE + slice2]) as usize;
+            let c = move_cost(m, self.metric);
+
+            // 枝刈り表はHTMの手数なので、QTMでも下界になる
+            if h + c > depth {
+                continue;
+            }
+
+            self.push(m);
+
+            if self.phase1(twist2, flip2, slice2, depth - c)? {
+                return Ok(true);
+            }
+
+            self.pop();
+        }
+
+        Ok(false)
+    }
+
+    fn start_phase2(&mut self) -> Result<bool, SolveError> {
+        if self.deadline.is_some_and(|d| Instant::now() > d) {
+            return Err(SolveError::Timeout);
+        }
+
+        let mut cs = self.cube.clone();
+
+        for m in &self.solution {
+            cs = cs.apply(&self.mt.moves[*m]);
+        }
+
+        let corners = cs.corners();
+        let ud_edges = cs.ud_edges();
+        let slice = cs.slice_sorted();
+
+        let h = self.t.corners_slice_prune[corners * N_SLICE_PERM + slice]
+            .max(self.t.ud_edges_slice_prune[ud_edges * N_SLICE_PERM + slice]) as usize;
+
+        for depth in h..=(self.max_length - self.cost).min(self.max_phase2) {
+            if self.phase2(corners, ud_edges, slice, depth) {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn phase2(&mut self, corners: usize, ud_edges: usize, slice: usize, depth: usize) -> bool {
+        if depth == 0 {
+            return corners == 0 && ud_edges == 0 && slice == 0;
+        }
+
+        for m in PHASE2_MOVES {
+            if !is_move_available(self.last_face(), m / 3) {
+                continue;
+            }
+
+            let corners2 = self.mt.corners_move[corners][m] as usize;
+            let ud_edges2 = self.mt.ud_edges_move[ud_edges][m] as usize;
+            let slice2 = self.mt.slice_sorted_move[slice][m] as usize;
+
+            let h = self.t.corners_slice_prune[corners2 * N_SLICE_PERM + slice2]
+                .max(self.t.ud_edges_slice_prune[ud_edges2 * N_SLICE_PERM + slice2]) as usize;
+            let c = move_cost(m, self.metric);
+
+            if h + c > depth {
+                continue;
+            }
+
+            self.push(m);
+
+            if self.phase2(corners2, ud_edges2, slice2, depth - c) {
+                return true;
+            }
+
+            self.pop();
+        }
+
+        false
+    }
+}
+
+/// 移動表と枝刈り表を作る。最初のsolveの前に呼んでおくと、solveの時間に表を作る時間が含まれない。
+pub fn init() {
+    move_tables();
+    tables();
+}
+
+/// 枝刈り表をcacheから読み込む。なければ作って保存する。
+/// すでに表がある場合(init_with_cacheかinitかsolveを呼んだ後)は何もしない。
+pub fn init_with_cache(cache: &TableCache) {
+    move_tables();
+    TABLES.get_or_init(|| build_tables(Some(cache)));
+}
+
+/// キューブをそろえる手順を求める。
+/// 持ち替えでセンターが動いている場合は、その向きのまま回す手順を返す。
+pub fn solve(st: &State, options: &SolveOptions) -> Result<Vec<Move>, SolveError> {
+    check_metric(options.metric)?;
+
+    let deadline = options.timeout.map(|t| Instant::now() + t);
+
+    // センターが元の位置に来るように持ち替えて解く
+    let (cube, rot) = normalize(st)?;
+    let normalized = cube.to_state();
+
+    let max_phase2 = match options.metric {
+        Metric::Qtm => MAX_PHASE2_LENGTH * 2,
+        _ => MAX_PHASE2_LENGTH,
+    };
+
+    // フェーズ1の難しさは向きで大きく変わるので、URFの対角線で3通りに持ち替えた状態と
+    // それぞれの逆の状態を同じ深さずつ探索し、最初に見つかった解を使う
+    let mut searches = vec![];
+
+    for rotation in diagonal_rotations() {
+        let rotated = CubieState::from_state(&normalized.rotate(rotation)).unwrap();
+
+        for inverse in [false, true] {
+            let cube = if inverse { rotated.get_prime() } else { rotated.clone() };
+
+            let search = Search {
+                mt: move_tables(),
+                t: tables(),
+                cube,
+                solution: vec![],
+                cost: 0,
+                max_length: options.max_length,
+                max_phase2,
+                metric: options.metric,
+                deadline,
+            };
+
+            searches.push((search, rotation, inverse));
+        }
+    }
+
+    for depth in 0..=options.max_length {
+        for (search, rotation, inverse) in &mut searches {
+            if search.start_phase1(depth)? {
+                return Ok(restore(&search.solution, *rotation, *inverse, rot));
+            }
+        }
+    }
+
+    // フェーズ2を制限したので見つからなかった可能性がある。
+    // max_length以内の解がないことを確かめるために、持ち替えていない状態を制限なしで探す。
+    let (search, _, _) = &mut searches[0];
+
+    if search.max_phase2 < options.max_length {
+        search.max_phase2 = options.max_length;
+
+        for depth in 0..=options.max_length {
+            if search.start_phase1(depth)? {
+                return Ok(denormalize(&search.solution, rot));
+            }
+        }
+    }
+
+    Err(SolveError::NotFound)
+}
+
+// 持ち替えない向きと、URFの対角線で120度、240度回す持ち替え
+fn diagonal_rotations() -> [Rotation; 3] {
+    let (x, y) = (Move::new(Layer::X, 1).unwrap(), Move::new(Layer::Y, 1).unwrap());
+
+    [Rotation::identity(), Rotation::from_moves(&[x, y]).unwrap(), Rotation::from_moves(&[x, y, x, y]).unwrap()]
+}
+
+// 持ち替えたか逆にした状態の解を、元の状態の解に直す。
+fn restore(solution: &[usize], rotation: Rotation, inverse: bool, rot: &State) -> Vec<Move> {
+    let mut alg = Algorithm::new(solution.iter().map(|m| FACE_MOVES[*m]).collect());
+
+    if inverse {
+        alg = alg.inverse();
+    }
+
+    let s = rotation.inverse().to_state().apply(rot);
+    alg.moves().iter().map(|mv| mv.conjugate(&s)).collect()
+}
@@ -0,0 +1,86 @@
+//! キューブの対称操作。24通りの持ち替えと、それぞれに鏡映を組み合わせた48通りがある。
+
+use crate::moves::{self, Axis, Move, Rotation};
+use crate::State;
+
+/// 48通りの対称操作のどれか。鏡映(RとLを入れ替える)をしてから持ち替える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symmetry {
+    rotation: Rotation,
+    mirrored: bool,
+}
+
+impl Symmetry {
+    /// 何もしない対称操作
+    pub fn identity() -> Symmetry {
+        Symmetry { rotation: Rotation::identity(), mirrored: false }
+    }
+
+    /// 48通りの対称操作。最初の24通りは鏡映を含まない。
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        [false, true].into_iter()
+            .flat_map(|mirrored| Rotation::all().map(move |rotation| Symmetry { rotation, mirrored }))
+    }
+
+    pub fn new(rotation: Rotation, mirrored: bool) -> Symmetry {
+        Symmetry { rotation, mirrored }
+    }
+
+    pub fn rotation(self) -> Rotation {
+        self.rotation
+    }
+
+    /// 鏡映を含むならtrue
+    pub fn is_mirrored(self) -> bool {
+        self.mirrored
+    }
+
+    /// 対称操作を表す置換
+    pub fn to_state(self) -> State {
+        let r = self.rotation.to_state();
+
+        if self.mirrored {
+            moves::reflection(Axis::X).apply(&r)
+        } else {
+            r
+        }
+    }
+
+    /// 逆の対称操作
+    pub fn inverse(self) -> Symmetry {
+        let st = self.to_state().get_prime();
+        Symmetry::all().find(|s| s.to_state() == st).unwrap()
+    }
+
+    /// selfの後にotherをした対称操作
+    pub fn then(self, other: Symmetry) -> Symmetry {
+        let st = self.to_state().apply(&other.to_state());
+        Symmetry::all().find(|s| s.to_state() == st).unwrap()
+    }
+
+    /// 対称操作で移した状態(対称操作の逆、st、対称操作の順に動かすのと同じ状態)。
+    /// State::mirror(Axis::X)してからState::rotateしたものと同じになる。
+    pub fn conjugate(self, st: &State) -> State {
+        let st = if self.mirrored { st.mirror(Axis::X) } else { *st };
+        st.rotate(self.rotation)
+    }
+
+    /// 対称操作で移した動き
+    pub fn conjugate_move(self, mv: Move) -> Move {
+        mv.conjugate(&self.to_state())
+    }
+}
+
+/// 対称操作で移した状態のうち、置換が辞書順で最小のものと、そこへ移す対称操作を返す。
+/// 同じ類の状態は同じ代表元になる。
+pub fn representative(st: &State) -> (State, Symmetry) {
+    Symmetry::all()
+        .map(|s| (s.conjugate(st), s))
+        .min_by_key(|(st, _)| st.get_p())
+        .unwrap()
+}
+
+/// 状態を変えない対称操作の集まり(固定部分群)。恒等操作を必ず含む。
+pub fn stabilizer(st: &State) -> Vec<Symmetry> {
+    Symmetry::all().filter(|s| s.conjugate(st) == *st).collect()
+}
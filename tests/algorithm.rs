@@ -0,0 +1,179 @@
+use cube::algorithm::{Algorithm, Metric, SimplifyOptions};
+use cube::moves::{Axis, Layer, Move, Rotation};
+use cube::State;
+
+fn alg(s: &str) -> Algorithm {
+    s.parse().unwrap()
+}
+
+fn simplify(s: &str, options: &SimplifyOptions) -> String {
+    let a = alg(s);
+    let simplified = a.simplify(options);
+
+    // 書き直しても同じ状態になる
+    let solved = State::new_solved();
+    assert_eq!(solved.apply_algorithm(&simplified), solved.apply_algorithm(&a), "{}", s);
+
+    simplified.to_string()
+}
+
+#[test]
+fn test_simplify() {
+    let options = SimplifyOptions::default();
+
+    for (s, expected) in [
+        ("R U U' R2 L R", "L"),
+        ("R U U' R'", ""),
+        ("R L R", "R2 L"),
+        ("L R L' R'", ""),
+        ("U D U D' U2", ""),
+        ("D U D", "U D2"),
+        ("R3 R3", "R2"),
+        ("F R U R' U' F'", "F R U R' U' F'"),
+        ("M M' Rw Rw' x x'", ""),
+        ("R M R' Rw M' Rw", "Rw2"),
+        ("R Rw R'", "Rw"),
+        // 種類の違う動きはまとめない
+        ("R M' L'", "R L' M'"),
+        ("y U'", "U' y"),
+    ] {
+        assert_eq!(simplify(s, &options), expected, "{}", s);
+    }
+
+    assert_eq!(alg("").simplify(&options), Algorithm::default());
+}
+
+#[test]
+fn test_simplify_rewrite() {
+    let wide = SimplifyOptions { rewrite_wide: true, ..Default::default() };
+    let all = SimplifyOptions { rewrite_wide: true, rewrite_rotations: true, ..Default::default() };
+    let etm = SimplifyOptions { metric: Metric::Etm, ..all.clone() };
+    let qtm = SimplifyOptions { metric: Metric::Qtm, ..all.clone() };
+
+    assert_eq!(simplify("Rw R'", &wide), "M'");
+    assert_eq!(simplify("R M'", &wide), "Rw");
+    assert_eq!(simplify("U E' D'", &wide), "D' Uw");
+    assert_eq!(simplify("R2 L2 M2", &wide), "L2 Rw2");
+    assert_eq!(simplify("y U'", &wide), "U' y");
+
+    assert_eq!(simplify("R M' L'", &all), "x");
+    assert_eq!(simplify("U E' D'", &all), "y");
+    assert_eq!(simplify("y U'", &all), "Dw'");
+    assert_eq!(simplify("R L' x'", &all), "M");
+    assert_eq!(simplify("R2 L2 M2", &all), "x2");
+    assert_eq!(simplify("R L'", &all), "R L'");
+
+    // 数え方によって短い書き方が変わる
+    assert_eq!(simplify("R M x'", &all), "L' Lw2");
+    assert_eq!(simplify("R M x'", &etm), "L M2");
+    assert_eq!(simplify("R M x'", &qtm), "L' Lw2");
+
+    let sexy = alg("R U R' U'");
+    assert_eq!(sexy.simplify(&etm), sexy);
+
+    // 手順と逆の手順をつなげると消える
+    assert_eq!(simplify("R U R' U' U R U' R'", &all), "");
+
+    // 2回simplifyしても変わらない
+    for s in ["Rw U R' U' Rw' F R F'", "R M x' U E' D' y", "L R L R2 M'"] {
+        let once = alg(s).simplify(&all);
+        assert_eq!(once.simplify(&all), once);
+    }
+}
+
+#[test]
+fn test_transforms() {
+    let solved = State::new_solved();
+    let y = Rotation::from_moves(&[Move::new(Layer::Y, 1).unwrap()]).unwrap();
+
+    assert_eq!(alg("R U2 R' F").inverse(), alg("F' R U2' R'"));
+    assert!(solved.apply_algorithm(&alg("R U R' U' M2 Rw x").inverse()).apply_algorithm(&alg("R U R' U' M2 Rw x")).is_solved0());
+
+    assert_eq!(alg("R U R' U'").mirror(Axis::X), alg("L' U' L U"));
+    assert_eq!(alg("R U R' U'").mirror(Axis::Z), alg("R' U' R U"));
+    assert_eq!(alg("R U R' U'").mirror(Axis::Y), alg("R' D' R D"));
+    assert_eq!(alg("Rw M x S").mirror(Axis::X), alg("Lw' M x S'"));
+    assert_eq!(alg("R U F").rotate(y), alg("F U L"));
+    assert_eq!(alg("R U F").rotate(y.inverse()), alg("B U R"));
+
+    for s in ["R U R' U'", "F R U' R' U' R U R' F' R U R' U' R' F R F'", "Rw U M' x y' S2 Dw E' Lw2 z"] {
+        let a = alg(s);
+        let st = solved.apply_algorithm(&a);
+
+        assert_eq!(solved.apply_algorithm(&a.inverse()), st.get_prime());
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert_eq!(solved.apply_algorithm(&a.mirror(axis)), st.mirror(axis), "{} {:?}", s, axis);
+            assert_eq!(a.mirror(axis).mirror(axis), a);
+        }
+
+        for rot in Rotation::all() {
+            assert_eq!(solved.apply_algorithm(&a.rotate(rot)), st.rotate(rot), "{}", s);
+            // rotationの後に書き直した手順を回すのと、元の手順の後にrotationするのは同じ
+            assert_eq!(rot.to_state().apply(&solved.apply_algorithm(&a.rotate(rot))), st.apply(&rot.to_state()));
+        }
+    }
+
+    assert_eq!(Rotation::all().count(), 24);
+    assert_eq!(Rotation::from_moves(alg("x y x'").moves()), Rotation::from_moves(alg("z").moves()));
+    assert_eq!(Rotation::from_moves(alg("x R").moves()), None);
+    assert_eq!(Rotation::from_moves(&[]), Some(Rotation::identity()));
+}
+
+#[test]
+fn test_count() {
+    let a = alg("R U2 M' x Rw2 E2 y'");
+
+    assert_eq!(a.len(), 7);
+    assert_eq!(a.count(Metric::Htm), 7);  // スライスは2手、持ち替えは0手
+    assert_eq!(a.count(Metric::Qtm), 11);
+    assert_eq!(a.count(Metric::Stm), 5);
+    assert_eq!(a.count(Metric::Etm), 7);
+    assert_eq!(Metric::Qtm.cost(Move::new(Layer::R, 3).unwrap()), 1);
+    assert_eq!(Algorithm::default().count(Metric::Etm), 0);
+}
+
+#[test]
+fn test_remove_rotations() {
+    let solved = State::new_solved();
+
+    for s in [
+        "R U R' U'",
+        "x R U R' y2 F",
+        "Rw U Rw' U' r' F R F'",
+        "M2 U M U2 M' U M2",
+        "E S' Dw Lw' Bw2 Fw' Uw E2 S2 M'",
+        "z y' x2 R U2 x' D",
+    ] {
+        let a = alg(s);
+        let (faces, rot) = a.remove_rotations();
+
+        assert!(faces.moves().iter().all(|mv| mv.layer().is_face() && !mv.is_wide()), "{}", faces);
+        assert_eq!(solved.apply_algorithm(&faces).apply(&rot.to_state()), solved.apply_algorithm(&a), "{}", s);
+
+        // 最後の持ち替えの動きをつなげると元の手順と同じ状態
+        let mut with_rot = faces.moves().to_vec();
+        with_rot.extend(rot.moves());
+        assert_eq!(solved.apply_algorithm(&Algorithm::new(with_rot)), solved.apply_algorithm(&a));
+    }
+
+    assert_eq!(alg("y R").remove_rotations().0, alg("B"));
+    assert_eq!(alg("Rw").remove_rotations(), (alg("L"), Rotation::from_moves(alg("x").moves()).unwrap()));
+    assert_eq!(alg("M2").remove_rotations().0, alg("R2 L2"));
+
+    // 面の動きだけにすると、そろえる手順の後にis_solved0で確かめられる
+    let scramble = solved.apply_algorithm(&alg("R U R' F2 D2 L"));
+    let x = Rotation::from_moves(alg("x").moves()).unwrap();
+    let rotated = format!("x {} y", alg("L' D2 F2 R U' R'").rotate(x));
+    assert!(scramble.apply_algorithm(&alg(&rotated)).is_solved());
+    assert!(!scramble.apply_algorithm(&alg(&rotated)).is_solved0());
+
+    let (faces, _) = alg(&rotated).remove_rotations();
+    assert_eq!(faces, alg("L' D2 F2 R U' R'"));
+    assert!(scramble.apply_algorithm(&faces).is_solved0());
+
+    for rot in Rotation::all() {
+        assert!(rot.moves().len() <= 2);
+        assert_eq!(Rotation::from_moves(&rot.moves()), Some(rot));
+    }
+}
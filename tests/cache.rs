@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cube::solver::cache::TableCache;
+use cube::solver::optimal::{DatabaseOptions, OptimalSolver};
+use cube::solver::SolveOptions;
+use cube::State;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cube_cache_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_cache() {
+    let dir = temp_dir("optimal");
+    let cache = TableCache::new(&dir);
+    assert_eq!(cache.dir(), dir);
+
+    let db_options = DatabaseOptions { corners: false, edges: 4 };
+    let options = SolveOptions::optimal();
+    let cb = State::new_solved().apply_moves("R U R' F2 D2 L").unwrap();
+
+    // 1回目は作って保存する
+    let solver = OptimalSolver::with_cache(&db_options, &cache);
+    let files: Vec<PathBuf> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| f.extension().unwrap() == "tbl"));
+
+    let solution = solver.solve(&cb, &options).unwrap();
+
+    // 2回目は読み込んだ表で同じ結果になる
+    let loaded = OptimalSolver::with_cache(&db_options, &cache);
+    assert_eq!(loaded.solve(&cb, &options).unwrap(), solution);
+
+    // 壊れたファイルは作り直す
+    let saved: Vec<Vec<u8>> = files.iter().map(|f| fs::read(f).unwrap()).collect();
+
+    for f in &files {
+        let mut data = fs::read(f).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        fs::write(f, &data).unwrap();
+    }
+
+    let rebuilt = OptimalSolver::with_cache(&db_options, &cache);
+    assert_eq!(rebuilt.solve(&cb, &options).unwrap(), solution);
+
+    // 作り直した表は保存されている
+    for (f, data) in files.iter().zip(saved) {
+        assert_eq!(fs::read(f).unwrap(), data);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cache_concurrent() {
+    let dir = temp_dir("concurrent");
+    let cache = TableCache::new(&dir);
+    let db_options = DatabaseOptions { corners: false, edges: 4 };
+
+    // 同じ表を同時に保存しても一時ファイルが重ならない
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| OptimalSolver::with_cache(&db_options, &cache));
+        }
+    });
+
+    let files: Vec<PathBuf> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| f.extension().unwrap() == "tbl"));
+
+    let cb = State::new_solved().apply_moves("R U R' F2").unwrap();
+    let loaded = OptimalSolver::with_cache(&db_options, &cache);
+    assert_eq!(loaded.solve(&cb, &SolveOptions::optimal()).unwrap().len(), 4);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,61 @@
+use std::collections::{BTreeSet, HashSet};
+
+use cube::moves::Rotation;
+use cube::{Canonical, State};
+
+#[test]
+fn test_canonical() {
+    let solved = State::new_solved();
+
+    assert_eq!(solved.orientation(), Some(Rotation::identity()));
+    assert_eq!((&solved * "x y").canonical(), solved);
+    assert!((&solved * "x y").is_solved());
+    assert!(!(&solved * "x y").is_solved0());
+
+    // 持ち替えの前後でそろえ方は変わらない
+    let cb = &solved * "R U R' F2 D2 L";
+    assert!(cb.eq_modulo_rotation(&(&cb * "z2 y'")));
+    assert!((&solved * "R U R'").eq_modulo_rotation(&(&solved * "y F U F'")));
+    assert!(!cb.eq_modulo_rotation(&(&solved * "R U R' F2 D2 L'")));
+
+    // ワイドムーブとスライスは面の動きと持ち替えの組み合わせ
+    assert!((&solved * "Rw").eq_modulo_rotation(&(&solved * "L")));
+    assert!((&solved * "M").eq_modulo_rotation(&(&solved * "R L'")));
+
+    // 持ち替えで作れないセンターの配置
+    let st = State::product_of_cycles(&vec![vec![4, 13]]);
+    assert_eq!(st.orientation(), None);
+    assert_eq!(st.canonical(), st);
+    assert!(!st.is_solved());
+}
+
+#[test]
+fn test_canonical_hash() {
+    let solved = State::new_solved();
+    let set: HashSet<Canonical> = Rotation::all()
+        .map(|r| Canonical::new(&(&solved * "R U").apply(&r.to_state())))
+        .collect();
+
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&Canonical::from(&solved * "R U")));
+    assert!(!set.contains(&Canonical::from(&solved * "U R")));
+    assert_eq!(set.iter().next().unwrap().state(), &(&solved * "R U"));
+}
+
+#[test]
+fn test_collections() {
+    // 2手以内で作れる状態を数える
+    let solved = State::new_solved();
+    let faces = ["U", "D", "F", "B", "R", "L"];
+    let mut states = BTreeSet::from([solved]);
+
+    for _ in 0..2 {
+        let next: Vec<State> = states.iter()
+            .flat_map(|st| faces.iter().flat_map(move |f| ["", "2", "'"].map(|a| st * format!("{}{}", f, a).as_str())))
+            .collect();
+        states.extend(next);
+    }
+
+    assert_eq!(states.len(), 1 + 18 + 243);
+    assert_eq!(states.iter().copied().collect::<HashSet<_>>().len(), states.len());
+}
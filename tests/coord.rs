@@ -0,0 +1,56 @@
+use cube::coord::N_STATES;
+use cube::cubie::CubieState;
+use cube::solvable::SolvableError;
+use cube::State;
+
+#[test]
+fn test_rank() {
+    let solved = State::new_solved();
+    assert_eq!(solved.rank(), Ok(0));
+    assert_eq!(State::unrank(0), Some(solved));
+
+    for scramble in [
+        "R",
+        "R U R' F2 D2 L",
+        "U' F' D2 R U2 R' U2 F2 R D2 L2 D2 R' B U' L' B2 D2 B2 U2",
+        "F R' B2 L U' D F2 R B' U2 L D' R2 F B' U L2 D B R'",
+    ] {
+        let cb = &solved * scramble;
+        let rank = cb.rank().unwrap();
+
+        assert!(rank < N_STATES);
+        assert_eq!(State::unrank(rank), Some(cb));
+
+        // 持ち替えても番号は変わらない
+        assert_eq!((&cb * "x y").rank(), Ok(rank));
+    }
+
+    for rank in [1, 2, 3, 12345678901234567890, N_STATES / 2, N_STATES - 1] {
+        let st = State::unrank(rank).unwrap();
+        assert_eq!(st.check_solvable(), Ok(()));
+        assert_eq!(st.rank(), Ok(rank));
+    }
+
+    assert_eq!(State::unrank(N_STATES), None);
+    assert!(State::product_of_cycles(&vec![vec![5, 19]]).rank().is_err());
+
+    let mut cs = CubieState::new_solved();
+    cs.co[0] = 1;
+    assert_eq!(cs.rank(), Err(SolvableError::CornerTwist));
+}
+
+#[test]
+fn test_coords() {
+    let solved = CubieState::new_solved();
+    assert_eq!((solved.twist(), solved.flip(), solved.slice(), solved.corners(), solved.edges()), (0, 0, 0, 0, 0));
+
+    // Rはエッジの向きを変えず、コーナーの向きを変える
+    let cs = CubieState::from_state(&(&State::new_solved() * "R")).unwrap();
+    assert_eq!(cs.flip(), 0);
+    assert_ne!(cs.twist(), 0);
+    assert_ne!(cs.slice(), 0);
+
+    // Fはエッジの向きを変える
+    let cs = CubieState::from_state(&(&State::new_solved() * "F")).unwrap();
+    assert_ne!(cs.flip(), 0);
+}
@@ -0,0 +1,52 @@
+use cube::cubie::CubieState;
+use cube::solvable::SolvableError;
+
+#[test]
+fn test_cubie_conversion() {
+    let solved = cube::State::new_solved();
+    assert!(CubieState::from_state(&solved).unwrap().is_solved());
+
+    let scramble = "U' F' D2 R U2 R' U2 F2 R D2 L2 D2 R' B U' L' B2 D2 B2 U2";
+    let cb = &solved * scramble;
+    let cs = CubieState::from_state(&cb).unwrap();
+
+    // 状態に戻すと元と一致する
+    assert_eq!(cs.to_state(), cb);
+
+    // ねじれと反転の合計は0になる
+    assert_eq!(cs.co.iter().map(|v| *v as u32).sum::<u32>() % 3, 0);
+    assert_eq!(cs.eo.iter().map(|v| *v as u32).sum::<u32>() % 2, 0);
+}
+
+#[test]
+fn test_cubie_moves() {
+    let solved = cube::State::new_solved();
+
+    // Rはコーナーをねじり、Fはエッジも反転させる
+    let r = CubieState::from_state(&(&solved * "R")).unwrap();
+    assert_eq!(r.cp, [4, 1, 2, 0, 7, 5, 6, 3]);
+    assert_eq!(r.co, [2, 0, 0, 1, 1, 0, 0, 2]);
+    assert_eq!(r.eo, [0; 12]);
+
+    let f = CubieState::from_state(&(&solved * "F")).unwrap();
+    assert_eq!(f.eo, [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0]);
+
+    // applyとget_primeはStateと同じ結果になる
+    let a = &solved * "R U R' F2 D2 L";
+    let b = &solved * "B' L2 D F U2";
+    let ca = CubieState::from_state(&a).unwrap();
+    let cb = CubieState::from_state(&b).unwrap();
+
+    assert_eq!((&ca * &cb).to_state(), &a * &b);
+    assert_eq!(ca.get_prime().to_state(), a.get_prime());
+    assert!((&ca * &ca.get_prime()).is_solved());
+}
+
+#[test]
+fn test_cubie_centers_moved() {
+    // センターが動いているときは変換できない
+    let solved = cube::State::new_solved();
+    assert!(CubieState::from_state(&(&solved * "M")).is_err());
+    assert!(CubieState::from_state(&(&solved * "x")).is_err());
+    assert_eq!(CubieState::from_state(&(&solved * "y")), Err(SolvableError::CentersMoved));
+}
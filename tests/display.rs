@@ -0,0 +1,43 @@
+use cube::scheme::{ColorScheme, Mask};
+use cube::State;
+
+#[test]
+fn test_display() {
+    let solved = State::new_solved();
+
+    let expected = "    WWW
+    WWW
+    WWW
+OOO GGG RRR BBB
+OOO GGG RRR BBB
+OOO GGG RRR BBB
+    YYY
+    YYY
+    YYY";
+    assert_eq!(solved.to_string(), expected);
+
+    let r = &solved * "R";
+    assert_eq!(r.to_string().lines().next(), Some("    WWG"));
+    assert_eq!(r.to_string().lines().nth(3), Some("OOO GGY RRR WBB"));
+
+    // {:#}はANSIエスケープで色を付ける
+    let colored = format!("{:#}", solved);
+    assert_eq!(colored.lines().next(), Some("    \x1b[38;2;255;255;255mW\x1b[0m\x1b[38;2;255;255;255mW\x1b[0m\x1b[38;2;255;255;255mW\x1b[0m"));
+    assert_ne!(colored, expected);
+
+    let japanese = ColorScheme::japanese();
+    let text = solved.display_with(&japanese, &Mask::oll()).to_string();
+    assert_eq!(text.lines().nth(3), Some("--- --- --- ---"));
+    assert_eq!(text.lines().last(), Some("    ---"));
+}
+
+#[test]
+fn test_cycle_notation() {
+    let solved = State::new_solved();
+    assert_eq!(solved.cycle_notation().to_string(), "()");
+
+    let u = &solved * "U";
+    assert_eq!(u.cycle_notation().0, u.get_cycles());
+    assert!(u.cycle_notation().to_string().starts_with("(0 2 8 6)(1 5 7 3)"));
+    assert_eq!(State::product_of_cycles(&vec![vec![1, 2], vec![3, 4, 5]]).cycle_notation().to_string(), "(1 2)(3 4 5)");
+}
@@ -0,0 +1,104 @@
+use cube::facelet::FaceletError;
+use cube::solvable::SolvableError;
+use cube::State;
+
+const SOLVED: &str = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+
+#[test]
+fn test_to_facelets() {
+    let solved = State::new_solved();
+    assert_eq!(solved.to_facelets(), SOLVED);
+
+    let r = &solved * "R";
+    assert_eq!(r.to_facelets(), "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB");
+
+    // 持ち替えても同じ文字列になる
+    assert_eq!((&solved * "x y").to_facelets(), SOLVED);
+    assert_eq!((&solved * "r").to_facelets(), (&solved * "L").to_facelets());
+}
+
+#[test]
+fn test_from_facelets() {
+    let solved = State::new_solved();
+    assert_eq!(State::from_facelets(SOLVED), Ok(solved));
+
+    let scramble = "U' F' D2 R U2 R' U2 F2 R D2 L2 D2 R' B U' L' B2 D2 B2 U2";
+    let cb = &solved * scramble;
+    let s = cb.to_facelets();
+    assert_eq!(&s[0..9], "LBDDUBURF");
+    assert_eq!(State::from_facelets(&s), Ok(cb));
+
+    let st = State::from_facelets("DUUBULDBFRBFRRULLLBRDFFFBLURDBFDFDRFRULBLUFDURRBLBDUDL").unwrap();
+    assert_eq!(st.to_facelets(), "DUUBULDBFRBFRRULLLBRDFFFBLURDBFDFDRFRULBLUFDURRBLBDUDL");
+}
+
+#[test]
+fn test_facelet_errors() {
+    assert_eq!(State::from_facelets("UUU"), Err(FaceletError::InvalidLength(3)));
+    assert_eq!(State::from_facelets(&SOLVED.replacen('R', "X", 1)),
+        Err(FaceletError::InvalidChar { index: 9, ch: 'X' }));
+    assert_eq!(State::from_facelets(&SOLVED.replacen('R', "U", 1)),
+        Err(FaceletError::InvalidCount { face: 'U', count: 10 }));
+
+    // URFのUとFを入れ替えると鏡像のコーナーになる
+    let mut s: Vec<char> = SOLVED.chars().collect();
+    s.swap(8, 20);
+    let s: String = s.into_iter().collect();
+    assert_eq!(State::from_facelets(&s), Err(FaceletError::InvalidCorner { position: 0 }));
+
+    // エッジを1つだけ反転する
+    let mut s: Vec<char> = SOLVED.chars().collect();
+    s.swap(5, 10);
+    let s: String = s.into_iter().collect();
+    assert_eq!(State::from_facelets(&s), Err(FaceletError::Unsolvable(SolvableError::EdgeFlip)));
+}
+
+#[test]
+fn test_from_face_colors() {
+    use cube::Face;
+
+    let solved = State::new_solved();
+    let scramble = "U' F' D2 R U2 R' U2 F2 R D2 L2 D2 R' B U' L' B2 D2 B2 U2";
+
+    for cb in [solved, &solved * scramble, &solved * "R U R' y M2"] {
+        let st = State::from_face_colors(cb.get_face_colors(Face::Up), cb.get_face_colors(Face::Front),
+            cb.get_face_colors(Face::Right), cb.get_face_colors(Face::Down),
+            cb.get_face_colors(Face::Back), cb.get_face_colors(Face::Left));
+        assert_eq!(st, Ok(cb));
+    }
+}
+
+#[test]
+fn test_face_colors_errors() {
+    use cube::Color;
+    use cube::Face;
+
+    let solved = State::new_solved();
+    let up = solved.get_face_colors(Face::Up);
+    let front = solved.get_face_colors(Face::Front);
+    let right = solved.get_face_colors(Face::Right);
+    let down = solved.get_face_colors(Face::Down);
+    let back = solved.get_face_colors(Face::Back);
+    let left = solved.get_face_colors(Face::Left);
+
+    let mut f = front;
+    f[0] = Color::Blue;
+    assert_eq!(State::from_face_colors(up, f, right, down, back, left),
+        Err(FaceletError::InvalidColorCount { color: Color::Green, count: 8 }));
+
+    // 白と黄色を持つコーナーは存在しない
+    let mut u = up;
+    let mut d = down;
+    u[6] = Color::Yellow;
+    d[0] = Color::White;
+    assert_eq!(State::from_face_colors(u, front, right, d, back, left),
+        Err(FaceletError::InvalidCorner { position: 1 }));
+
+    // UFLの位置にもURFと同じ色のコーナーがある
+    let (mut f, mut r, mut l) = (front, right, left);
+    f[0] = Color::Red;
+    l[2] = Color::Green;
+    r[3] = Color::Orange;
+    assert_eq!(State::from_face_colors(up, f, r, down, back, l),
+        Err(FaceletError::DuplicateCorner { position: 1, other: 0 }));
+}
@@ -0,0 +1,160 @@
+use cube::notation::{parse, Layer, Move, Node, ParseError, ParseErrorKind, Sequence, Token, MAX_DEPTH, MAX_MOVES, MAX_REPEAT};
+use cube::moves::MOVES;
+use cube::State;
+
+fn mv(layer: Layer, amount: u8) -> Move {
+    Move::new(layer, amount).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(parse("").unwrap(), Sequence::default());
+    assert_eq!(parse("  ").unwrap(), Sequence::default());
+
+    assert_eq!(parse("R U R'U'").unwrap().moves(),
+               vec![mv(Layer::R, 1), mv(Layer::U, 1), mv(Layer::R, 3), mv(Layer::U, 3)]);
+
+    for (s, amount) in [("R", 1), ("R2", 2), ("R'", 3), ("R2'", 2), ("R'2", 2), ("R3", 3), ("R3'", 1), ("R'3", 1)] {
+        assert_eq!(parse(s).unwrap().moves(), vec![mv(Layer::R, amount)], "{}", s);
+    }
+
+    assert_eq!(parse("Rw r2 x' M2").unwrap().moves(),
+               vec![Move::new_wide(Layer::R, 1).unwrap(), Move::new_wide(Layer::R, 2).unwrap(), mv(Layer::X, 3), mv(Layer::M, 2)]);
+
+    let seq = parse("F (R U')(U R') ((L))").unwrap();
+    assert_eq!(seq.nodes.len(), 4);
+    assert_eq!(seq.nodes[1], Node::Group(parse("R U'").unwrap()));
+    assert_eq!(seq.moves().len(), 6);
+    assert_eq!(seq.to_string(), "F (R U') (U R') ((L))");
+    assert_eq!(seq.to_string().parse::<Sequence>().unwrap(), seq);
+}
+
+#[test]
+fn test_compound() {
+    let expand = |s: &str| parse(s).unwrap().moves();
+
+    assert_eq!(expand("[R U R', D]"), expand("R U R' D R U' R' D'"));
+    assert_eq!(expand("[F: R U R' U']"), expand("F R U R' U' F'"));
+    assert_eq!(expand("(R U R' U')3"), expand("R U R' U' R U R' U' R U R' U'"));
+    assert_eq!(expand("[R U R', D]2"), expand("[R U R', D] [R U R', D]"));
+    assert_eq!(expand("(R)0"), vec![]);
+
+    // 入れ子
+    assert_eq!(expand("[R: [U, R D R']]"), expand("R U R D R' U' R D' R' R'"));
+    assert_eq!(expand("[[R, U]: F]"), expand("R U R' U' F U R U' R'"));
+    assert_eq!(expand("[F, (R U)2]"), expand("F R U R U F' U' R' U' R'"));
+
+    let seq = parse("[R U R',D]  [F:R U R'U'] (R U R' U')3 [x, [U, M']]10").unwrap();
+    assert!(matches!(&seq.nodes[2], Node::Repeat(node, 3) if matches!(**node, Node::Group(_))));
+    assert_eq!(seq.to_string(), "[R U R', D] [F: R U R' U'] (R U R' U')3 [x, [U, M']]10");
+    assert_eq!(seq.to_string().parse::<Sequence>().unwrap(), seq);
+
+    // 展開した手順でも状態に適用しても同じ
+    let solved = State::new_solved();
+    let a = solved.apply_moves("[R U R', D] [F: R U R' U'] (R U R' U')3").unwrap();
+    let b = solved.apply_moves(&parse("[R U R', D] [F: R U R' U'] (R U R' U')3").unwrap()
+        .moves().iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ")).unwrap();
+    assert_eq!(a, b);
+    assert!(solved.apply_moves("(R U R' U')6").unwrap().is_solved());
+}
+
+#[test]
+fn test_move() {
+    let solved = State::new_solved();
+
+    for m in [MOVES::X, MOVES::U2, MOVES::RPrime, MOVES::Lw, MOVES::Dw2, MOVES::MPrime, MOVES::S] {
+        let mv = Move::from(m);
+        assert_eq!(MOVES::from(mv), m);
+        assert_eq!(mv.to_string(), m.name());
+        assert_eq!(m.name().parse::<Move>(), Ok(mv));
+        assert_eq!(solved.apply_move(mv), State::get_move(m));
+        assert_eq!(State::get_move_by_name(m.name()), Some(State::get_move(mv)));
+    }
+
+    let rw = "r'".parse::<Move>().unwrap();
+    assert_eq!((rw.layer(), rw.amount(), rw.is_wide()), (Layer::R, 3, true));
+    assert_eq!(rw.inverse(), "Rw".parse().unwrap());
+    assert_eq!(Move::new_wide(Layer::M, 1), None);
+    assert_eq!(Move::new(Layer::U, 4), None);
+
+    assert_eq!("R U".parse::<Move>().unwrap_err().offset, 1);
+    assert_eq!("".parse::<Move>().unwrap_err().expected, vec![Token::Move]);
+    assert_eq!(State::get_move_by_name("Rw w"), None);
+}
+
+#[test]
+fn test_apply() {
+    let solved = State::new_solved();
+
+    assert_eq!(solved.apply_moves("R U R'U'").unwrap(), solved.apply_moves("R U R' U'").unwrap());
+    assert_eq!(solved.apply_moves("R2'").unwrap(), solved.apply_moves("R2").unwrap());
+    assert_eq!(solved.apply_moves("R3").unwrap(), solved.apply_moves("R'").unwrap());
+    assert_eq!(solved.apply_moves("(R U)(R U)").unwrap(), solved.apply_moves("R U R U").unwrap());
+    assert_eq!(solved.apply_moves("r").unwrap(), solved.apply_moves("Rw").unwrap());
+}
+
+#[test]
+fn test_parse_errors() {
+    let all = vec![Token::Wide, Token::Amount, Token::Prime, Token::Move, Token::OpenParen, Token::OpenBracket, Token::End];
+
+    assert_eq!(parse("R4"), Err(ParseError { kind: ParseErrorKind::Unexpected, offset: 1, found: Some('4'), expected: all }));
+    assert_eq!(parse("R2 Q"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 3, found: Some('Q'), expected: vec![Token::Move, Token::OpenParen, Token::OpenBracket, Token::End],
+    }));
+    assert_eq!(parse("R2'2"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 3, found: Some('2'), expected: vec![Token::Move, Token::OpenParen, Token::OpenBracket, Token::End],
+    }));
+    assert_eq!(parse("Rw'x"), Ok(parse("Rw' x").unwrap()));
+    assert_eq!(parse("(R U'"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 5, found: None, expected: vec![Token::Amount, Token::Move, Token::OpenParen, Token::OpenBracket, Token::CloseParen],
+    }));
+    assert_eq!(parse("R U)"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 3, found: Some(')'), expected: vec![Token::Wide, Token::Amount, Token::Prime, Token::Move, Token::OpenParen, Token::OpenBracket, Token::End],
+    }));
+
+    assert_eq!(parse("[R U"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 4, found: None,
+        expected: vec![Token::Wide, Token::Amount, Token::Prime, Token::Move, Token::OpenParen, Token::OpenBracket, Token::Comma, Token::Colon],
+    }));
+    assert_eq!(parse("[R, U: F]"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 5, found: Some(':'),
+        expected: vec![Token::Wide, Token::Amount, Token::Prime, Token::Move, Token::OpenParen, Token::OpenBracket, Token::CloseBracket],
+    }));
+    assert_eq!(parse("(R U) 3"), Err(ParseError {
+        kind: ParseErrorKind::Unexpected, offset: 6, found: Some('3'), expected: vec![Token::Move, Token::OpenParen, Token::OpenBracket, Token::End],
+    }));
+    assert_eq!(parse("(R)99999999999").unwrap_err().expected, vec![Token::Count]);
+
+    // 回数と展開した長さには上限がある
+    assert_eq!(parse("(R U)4000000000"), Err(ParseError {
+        kind: ParseErrorKind::TooLong, offset: 5, found: Some('4'), expected: vec![],
+    }));
+    assert_eq!(parse("((R)999)999").unwrap_err().offset, 8);
+    assert_eq!(parse("[[[[[[[[[[[[[[R, U], U], U], U], U], U], U], U], U], U], U], U], U], U]").unwrap_err().offset, 2);
+    assert_eq!(parse(&"(R)1000 ".repeat(11)).unwrap_err().offset, 80);
+    assert_eq!(parse("(R U)4000000000").unwrap_err().to_string(), "手順が長すぎる (5バイト目)");
+    assert_eq!(parse(&format!("(R){}", MAX_REPEAT)).unwrap().moves().len(), MAX_REPEAT as usize);
+    assert_eq!(parse(&"R".repeat(MAX_MOVES)).unwrap().moves().len(), MAX_MOVES);
+    assert_eq!(parse(&"R".repeat(MAX_MOVES + 1)).unwrap_err().offset, MAX_MOVES);
+
+    // 括弧の入れ子にも上限がある
+    let nested = |n: usize| format!("{}R{}", "(".repeat(n), ")".repeat(n));
+    assert!(parse(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(parse(&nested(MAX_DEPTH + 1)), Err(ParseError {
+        kind: ParseErrorKind::TooDeep, offset: MAX_DEPTH, found: Some('('), expected: vec![],
+    }));
+    assert_eq!(parse(&"(".repeat(100_000)).unwrap_err().kind, ParseErrorKind::TooDeep);
+    assert_eq!(parse(&"[".repeat(100_000)).unwrap_err().kind, ParseErrorKind::TooDeep);
+    assert_eq!(parse(&"[R: (".repeat(100_000)).unwrap_err().offset, MAX_DEPTH / 2 * 5);
+    let conjugates = |n: usize| format!("{}U{}", "[R: ".repeat(n), "]".repeat(n));
+    assert_eq!(parse(&conjugates(MAX_DEPTH)).unwrap().moves().len(), MAX_DEPTH * 2 + 1);
+    assert_eq!(parse(&conjugates(MAX_DEPTH + 1)).unwrap_err().offset, MAX_DEPTH * 4);
+    assert_eq!(parse(&nested(MAX_DEPTH + 1)).unwrap_err().to_string(), format!("括弧が深すぎる ({}バイト目)", MAX_DEPTH));
+
+    // 位置はバイト単位
+    assert_eq!(parse("R　Ü").unwrap_err().offset, 4);
+
+    let e = State::new_solved().apply_moves("R U R'U' R2'' F").unwrap_err();
+    assert_eq!(e.offset, 12);
+    assert_eq!(e.to_string(), "無効な文字: ' (12バイト目)、期待: 回転記号, (, [, 終わり");
+}
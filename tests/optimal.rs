@@ -0,0 +1,83 @@
+use cube::algorithm::{Algorithm, Metric};
+use cube::solver::optimal::{DatabaseOptions, OptimalSolver};
+use cube::solver::{two_phase, SolveError, SolveOptions};
+use cube::State;
+
+// テストでは小さいデータベースだけを使う
+fn small_solver() -> OptimalSolver {
+    OptimalSolver::new(&DatabaseOptions { corners: false, edges: 4 })
+}
+
+#[test]
+fn test_optimal() {
+    let solver = small_solver();
+    let options = SolveOptions::optimal();
+    let solved = State::new_solved();
+
+    assert_eq!(solver.solve(&solved, &options), Ok(vec![]));
+
+    for (scramble, distance) in [
+        ("R2", 1),
+        ("R U R'", 3),
+        ("R L U2 D'", 4),
+        ("R R R U U D2 D2 F'", 3),
+        ("R U R' F2 D2 L", 6),
+    ] {
+        let cb = &solved * scramble;
+        let solution = solver.solve(&cb, &options).unwrap();
+
+        assert_eq!(solution.len(), distance);
+        assert!(cb.apply_algorithm(&Algorithm::new(solution.clone())).is_solved0());
+
+        // 2フェーズの解より長くならない
+        let tp = two_phase::solve(&cb, &SolveOptions::two_phase()).unwrap();
+        assert!(solution.len() <= tp.len());
+    }
+
+    // 持ち替えた状態はその向きのままそろえる
+    let cb = &solved * "R U R' y";
+    let solution = solver.solve(&cb, &options).unwrap();
+    assert_eq!(solution.len(), 3);
+    assert!(cb.apply_algorithm(&Algorithm::new(solution)).is_solved());
+}
+
+#[test]
+fn test_optimal_errors() {
+    let solver = small_solver();
+
+    let st = State::product_of_cycles(&vec![vec![5, 19]]);
+    assert!(matches!(solver.solve(&st, &SolveOptions::optimal()), Err(SolveError::Unsolvable(_))));
+
+    let cb = &State::new_solved() * "R U R' F2 D2 L";
+    let options = SolveOptions { max_length: 5, ..SolveOptions::optimal() };
+    assert_eq!(solver.solve(&cb, &options), Err(SolveError::NotFound));
+
+    let options = SolveOptions { metric: Metric::Stm, ..SolveOptions::optimal() };
+    assert_eq!(solver.solve(&cb, &options), Err(SolveError::UnsupportedMetric(Metric::Stm)));
+}
+
+#[test]
+fn test_optimal_qtm() {
+    let solver = small_solver();
+    let qtm = SolveOptions { metric: Metric::Qtm, ..SolveOptions::optimal() };
+    let solved = State::new_solved();
+
+    for (scramble, distance) in [("R2", 2), ("R U2 R'", 4), ("R2 U'", 3), ("R U R' F2 D2 L", 8)] {
+        let cb = &solved * scramble;
+        let solution = solver.solve(&cb, &qtm).unwrap();
+
+        assert_eq!(Algorithm::new(solution.clone()).count(Metric::Qtm), distance, "{}", scramble);
+        assert!(cb.apply_algorithm(&Algorithm::new(solution)).is_solved0());
+    }
+}
+
+// データベースを作るのに時間がかかるので`cargo test --release -- --ignored`で実行する。
+#[test]
+#[ignore]
+fn test_optimal_full() {
+    let solver = OptimalSolver::default();
+    let cb = &State::new_solved() * "R U R' U R' F R F' U2 R' F R F'";
+
+    let solution = solver.solve(&cb, &SolveOptions::optimal()).unwrap();
+    assert_eq!(Algorithm::new(solution).count(Metric::Htm), 12);
+}
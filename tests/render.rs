@@ -0,0 +1,137 @@
+use cube::render::SvgOptions;
+use cube::scheme::{ColorScheme, Mask, Visibility};
+use cube::State;
+
+fn count(svg: &str, pat: &str) -> usize {
+    svg.matches(pat).count()
+}
+
+#[test]
+fn test_svg_net() {
+    let options = SvgOptions::default();
+    let svg = State::new_solved().to_svg(&options);
+
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(count(&svg, "<rect"), 54);
+
+    // 各色9枚
+    for color in ["#ffffff", "#009b48", "#b71234", "#ffd500", "#0046ad", "#ff5800"] {
+        assert_eq!(count(&svg, &format!(r##"fill="{}""##, color)), 9);
+    }
+
+    // 左上は上面の左上のステッカー
+    let size = 3.0 * 20.0 + 2.0 * 1.0;
+    assert!(svg.contains(&format!(r##"<rect x="{}" y="0.5" width="20" height="20" fill="#ffffff""##, 0.5 + size + 4.0)));
+    assert!(svg.contains(&format!(r#"width="{w}" height="{h}" viewBox="0 0 {w} {h}""#, w = 4.0 * size + 12.0 + 1.0, h = 3.0 * size + 8.0 + 1.0)));
+
+    // R: 前面の右の列が下面の色になる
+    let svg = (&State::new_solved() * "R").to_svg(&options);
+    let front_right = format!(r##"<rect x="{}" y="{}" width="20" height="20" fill="#ffd500""##, 0.5 + size + 4.0 + 42.0, 0.5 + size + 4.0);
+    assert!(svg.contains(&front_right));
+}
+
+#[test]
+fn test_svg_options() {
+    let options = SvgOptions {
+        sticker_size: 10.0,
+        gap: 0.0,
+        face_gap: 0.0,
+        border: 0.0,
+        background: Some((128, 128, 128)),
+        scheme: ColorScheme { rgb: [(1, 2, 3); 6], ..Default::default() },
+        ..Default::default()
+    };
+    let svg = State::new_solved().to_svg(&options);
+
+    assert!(svg.contains(r#"width="120" height="90""#));
+    assert!(svg.contains(r##"<rect width="100%" height="100%" fill="#808080"/>"##));
+    assert_eq!(count(&svg, r##"fill="#010203""##), 54);
+    assert!(!svg.contains("stroke"));
+}
+
+#[test]
+fn test_svg_isometric() {
+    let options = SvgOptions { gap: 0.0, ..Default::default() };
+    let svg = State::new_solved().to_isometric_svg(&options);
+
+    assert_eq!(count(&svg, "<polygon"), 27);
+    assert_eq!(count(&svg, r##"fill="#ffffff""##), 9);
+    assert_eq!(count(&svg, r##"fill="#009b48""##), 9);
+    assert_eq!(count(&svg, r##"fill="#b71234""##), 9);
+
+    // 上面の後ろの角が一番上、前の角が中心に来る
+    let cos = 20.0 * 30_f64.to_radians().cos();
+    assert!(svg.contains(&format!(r#"<polygon points="{:.2},0.50 "#, 0.5 + 3.0 * cos)));
+    assert!(svg.contains(&format!("{:.2},60.50", 0.5 + 3.0 * cos)));
+
+    // U: 前面の上の列に右面の色が来る
+    let svg = (&State::new_solved() * "U").to_isometric_svg(&options);
+    assert_eq!(count(&svg, r##"fill="#b71234""##), 6 + 3);
+    assert_eq!(count(&svg, r##"fill="#0046ad""##), 3);
+}
+
+#[test]
+fn test_svg_ll() {
+    let options = SvgOptions::default();
+    let svg = State::new_solved().to_ll_svg(&options);
+
+    assert_eq!(count(&svg, "<rect"), 9 + 12);
+    assert_eq!(count(&svg, r##"fill="#ffffff""##), 9);
+
+    for color in ["#009b48", "#b71234", "#0046ad", "#ff5800"] {
+        assert_eq!(count(&svg, &format!(r##"fill="{}""##, color)), 3);
+    }
+
+    // D面のステッカーは描かない
+    assert_eq!(count(&svg, r##"fill="#ffd500""##), 0);
+
+    // U: 前の列に右面、左の列に前面のステッカーが来る
+    let svg = (&State::new_solved() * "U").to_ll_svg(&options);
+    let x0 = 0.5 + 7.0 + 4.0;
+    let far = x0 + 62.0 + 4.0;
+    assert!(svg.contains(&format!(r##"<rect x="{}" y="{}" width="20" height="7" fill="#b71234""##, x0, far)));
+    assert!(svg.contains(&format!(r##"<rect x="0.5" y="{}" width="7" height="20" fill="#009b48""##, x0)));
+}
+
+#[test]
+fn test_svg_mask() {
+    let solved = State::new_solved();
+
+    // 最終層以外は灰色
+    let options = SvgOptions { mask: Mask::last_layer(), ..Default::default() };
+    let svg = solved.to_svg(&options);
+    assert_eq!(count(&svg, r##"fill="#808080""##), 54 - 21);
+
+    // マスクはステッカーについていく
+    let svg = (&solved * "R").to_svg(&options);
+    assert_eq!(count(&svg, r##"fill="#808080""##), 54 - 21);
+    assert_eq!(count(&svg, r##"fill="#ffffff""##), 9);
+
+    // 隠したステッカーは描かない
+    let options = SvgOptions { mask: Mask::from_fn(|i| if i < 9 { Visibility::Show } else { Visibility::Hide }), ..Default::default() };
+    assert_eq!(count(&solved.to_isometric_svg(&options), "<polygon"), 9);
+
+    let options = SvgOptions { mask: Mask::oll(), ..Default::default() };
+    let svg = solved.to_ll_svg(&options);
+    assert_eq!(count(&svg, r##"fill="#ffffff""##), 9);
+    assert_eq!(count(&svg, r##"fill="#808080""##), 12);
+}
+
+#[test]
+fn test_color_scheme() {
+    let solved = State::new_solved();
+    let japanese = ColorScheme::japanese();
+
+    assert_eq!(solved.get_face_colors_with(cube::Face::Down, &japanese), [cube::Color::Blue; 9]);
+    assert_eq!(solved.get_face_colors_with(cube::Face::Back, &japanese), [cube::Color::Yellow; 9]);
+    assert_eq!(solved.get_face_colors(cube::Face::Down), [cube::Color::Yellow; 9]);
+
+    let options = SvgOptions { scheme: ColorScheme::high_contrast(), ..Default::default() };
+    assert_eq!(count(&solved.to_svg(&options), r##"fill="#cc79a7""##), 9);
+
+    let mut mask = Mask::default();
+    mask.set(4, Visibility::Grey);
+    let stickers = solved.face_stickers(cube::Face::Up, &japanese, &mask);
+    assert_eq!(stickers.iter().map(|s| japanese.sticker_char(*s)).collect::<String>(), "WWWW-WWWW");
+}
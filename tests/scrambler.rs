@@ -0,0 +1,125 @@
+use cube::algorithm::Algorithm;
+use cube::moves::Layer;
+use cube::scramble::{RandomMoveOptions, RandomStateOptions, Scrambler, Subset};
+use cube::State;
+
+#[test]
+fn test_random_state() {
+    let mut scrambler = Scrambler::with_seed(1);
+    let states: Vec<State> = (0..100).map(|_| scrambler.random_state()).collect();
+
+    for st in &states {
+        assert_eq!(st.check_solvable(), Ok(()));
+        assert_eq!(st.orientation(), Some(cube::moves::Rotation::identity()));
+    }
+
+    // シードが同じなら同じ状態になる
+    let mut again = Scrambler::with_seed(1);
+    assert!(states.iter().all(|st| *st == again.random_state()));
+    assert_ne!(Scrambler::with_seed(2).random_state(), states[0]);
+}
+
+#[test]
+fn test_random_state_scramble() {
+    let mut scrambler = Scrambler::with_seed(42);
+    let options = RandomStateOptions::default();
+
+    let scrambles: Vec<Algorithm> = (0..3).map(|_| scrambler.random_state_scramble(&options).unwrap()).collect();
+
+    for scramble in &scrambles {
+        assert!(scramble.len() >= options.min_length);
+        assert!(scramble.len() <= options.solve.max_length);
+
+        // 文字列にしても同じ状態になる
+        let st = State::new_solved().apply_algorithm(scramble);
+        assert_eq!(State::new_solved().apply_moves(&scramble.to_string()), Ok(st));
+        assert!(!st.is_solved());
+    }
+
+    let mut again = Scrambler::with_seed(42);
+    assert_eq!(again.random_state_scramble(&options).unwrap(), scrambles[0]);
+}
+
+#[test]
+fn test_random_move_scramble() {
+    let mut scrambler = Scrambler::with_seed(7);
+    let options = RandomMoveOptions::default();
+    let scramble = scrambler.random_move_scramble(&options);
+
+    assert_eq!(scramble.len(), 25);
+
+    // 同じ面を続けて回さず、対面は決まった順番(U, F, R, D, B, L)で回す
+    let order = |l: Layer| [Layer::U, Layer::F, Layer::R, Layer::D, Layer::B, Layer::L].iter().position(|x| *x == l).unwrap();
+
+    for w in scramble.moves().windows(2) {
+        let (a, b) = (order(w[0].layer()), order(w[1].layer()));
+        assert!(a != b && !(a % 3 == b % 3 && a > b), "{}", scramble);
+    }
+
+    assert_eq!(Scrambler::with_seed(7).random_move_scramble(&options), scramble);
+}
+
+#[test]
+fn test_random_move_subset() {
+    let mut scrambler = Scrambler::with_seed(3);
+
+    let options = RandomMoveOptions { length: 30, layers: vec![Layer::R, Layer::U] };
+    let scramble = scrambler.random_move_scramble(&options);
+    assert_eq!(scramble.len(), 30);
+    assert!(scramble.moves().iter().all(|m| matches!(m.layer(), Layer::R | Layer::U)));
+
+    // <R, U>では交互に回すことになる
+    assert!(scramble.moves().windows(2).all(|w| w[0].layer() != w[1].layer()));
+
+    let options = RandomMoveOptions { length: 20, layers: vec![Layer::M, Layer::E, Layer::S] };
+    let scramble = scrambler.random_move_scramble(&options);
+    assert_eq!(scramble.len(), 20);
+    assert!(scramble.moves().iter().all(|m| !m.layer().is_face()));
+
+    // 同じ軸の層しかないと途中で回せる層がなくなる
+    let options = RandomMoveOptions { length: 10, layers: vec![Layer::U, Layer::D, Layer::E] };
+    assert!(scrambler.random_move_scramble(&options).len() <= 3);
+
+    let options = RandomMoveOptions { length: 10, layers: vec![Layer::X] };
+    assert!(scrambler.random_move_scramble(&options).is_empty());
+}
+
+#[test]
+fn test_subset_scramble() {
+    let mut scrambler = Scrambler::with_seed(5);
+    let options = RandomStateOptions::default();
+    let solved = State::new_solved();
+
+    // ランダムにしない部分のステッカー
+    let d_face: Vec<usize> = (27..36).collect();
+    let middle: Vec<usize> = [12, 13, 14, 21, 22, 23, 39, 40, 41, 48, 49, 50].to_vec();
+    let cross: Vec<usize> = [28, 30, 31, 32, 34, 16, 25, 43, 52].to_vec();
+    let corners: Vec<usize> = [0, 2, 6, 8, 9, 11, 15, 17, 18, 20, 24, 26, 27, 29, 33, 35, 36, 38, 42, 44, 45, 47, 51, 53].to_vec();
+    let edges: Vec<usize> = [1, 3, 5, 7, 10, 12, 14, 16, 19, 21, 23, 25, 28, 30, 32, 34, 37, 39, 41, 43, 46, 48, 50, 52].to_vec();
+
+    for (subset, fixed) in [
+        (Subset::LastLayer, [d_face.clone(), middle.clone()].concat()),
+        (Subset::Zbll, [d_face, middle].concat()),
+        (Subset::F2l, cross),
+        (Subset::Corners, edges),
+        (Subset::Edges, corners),
+    ] {
+        for _ in 0..2 {
+            let scramble = scrambler.subset_scramble(subset, &options).unwrap();
+            let cb = solved.apply_moves(&scramble.to_string()).unwrap();
+
+            assert!(!cb.is_solved(), "{:?}", subset);
+            assert_eq!(cb.orientation(), Some(cube::moves::Rotation::identity()));
+            assert!(fixed.iter().all(|i| cb.get_p()[*i] == *i as u8), "{:?} {}", subset, scramble);
+        }
+    }
+
+    // ZBLLはU面のエッジの向きがそろっている
+    for _ in 0..10 {
+        let cb = scrambler.subset_state(Subset::Zbll);
+        assert!([1, 3, 5, 7].iter().all(|i| (cb.get_p()[*i] as usize) < 9));
+    }
+
+    let mut again = Scrambler::with_seed(9);
+    assert_eq!(Scrambler::with_seed(9).subset_state(Subset::F2l), again.subset_state(Subset::F2l));
+}
@@ -0,0 +1,50 @@
+use cube::solvable::SolvableError;
+use cube::State;
+
+#[test]
+fn test_solvable() {
+    let solved = State::new_solved();
+    assert_eq!(solved.check_solvable(), Ok(()));
+
+    let scramble = "U' F' D2 R U2 R' U2 F2 R D2 L2 D2 R' B U' L' B2 D2 B2 U2";
+    assert_eq!((&solved * scramble).check_solvable(), Ok(()));
+
+    // 持ち替えやスライスでセンターが動いていてもよい
+    assert_eq!((&solved * "x y").check_solvable(), Ok(()));
+    assert_eq!((&solved * "M E S' r u'").check_solvable(), Ok(()));
+}
+
+#[test]
+fn test_unsolvable() {
+    // コーナーとエッジのステッカーを入れ替える
+    let st = State::product_of_cycles(&vec![vec![0, 1]]);
+    assert_eq!(st.check_solvable(), Err(SolvableError::MixedPieceType { sticker: 0 }));
+
+    // センターを2つ入れ替える
+    let st = State::product_of_cycles(&vec![vec![4, 13]]);
+    assert_eq!(st.check_solvable(), Err(SolvableError::CentersInconsistent));
+
+    // コーナーのステッカーを2枚だけ入れ替える
+    let st = State::product_of_cycles(&vec![vec![18, 11]]);
+    assert_eq!(st.check_solvable(), Err(SolvableError::InvalidCorner { position: 0 }));
+
+    // 2つのコーナーのステッカーを混ぜる
+    let st = State::product_of_cycles(&vec![vec![8, 6]]);
+    assert_eq!(st.check_solvable(), Err(SolvableError::InvalidCorner { position: 0 }));
+
+    // コーナーを1つだけねじる
+    let st = State::product_of_cycles(&vec![vec![8, 18, 11]]);
+    assert_eq!(st.check_solvable(), Err(SolvableError::CornerTwist));
+
+    // エッジを1つだけ反転する
+    let st = &State::product_of_cycles(&vec![vec![5, 19]]) * "R U";
+    assert_eq!(st.check_solvable(), Err(SolvableError::EdgeFlip));
+
+    // エッジを2つだけ入れ替える
+    let st = State::product_of_cycles(&vec![vec![5, 7], vec![19, 10]]);
+    assert_eq!(st.check_solvable(), Err(SolvableError::PermutationParity));
+
+    // 持ち替えても理由は変わらない
+    let st = &State::product_of_cycles(&vec![vec![5, 7], vec![19, 10]]) * "y x";
+    assert_eq!(st.check_solvable(), Err(SolvableError::PermutationParity));
+}
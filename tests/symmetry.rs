@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+use cube::moves::{Layer, Move, Rotation};
+use cube::symmetry::{self, Symmetry};
+use cube::State;
+
+#[test]
+fn test_symmetry() {
+    let states: HashSet<_> = Symmetry::all().map(|s| s.to_state().get_p()).collect();
+    assert_eq!(states.len(), 48);
+    assert_eq!(Symmetry::all().filter(|s| s.is_mirrored()).count(), 24);
+
+    let cb = &State::new_solved() * "R U R' F2 D2 L";
+
+    for s in Symmetry::all() {
+        assert_eq!(s.inverse().conjugate(&s.conjugate(&cb)), cb);
+        assert_eq!(s.then(s.inverse()), Symmetry::identity());
+
+        for t in Symmetry::all().step_by(5) {
+            assert_eq!(s.then(t).conjugate(&cb), t.conjugate(&s.conjugate(&cb)));
+        }
+
+        for layer in [Layer::U, Layer::R, Layer::M, Layer::X] {
+            let mv = Move::new(layer, 1).unwrap();
+            assert_eq!(s.conjugate(&State::get_move(mv)), State::get_move(s.conjugate_move(mv)));
+        }
+    }
+
+    // 鏡映でRは逆向きのLになる
+    let m = Symmetry::new(Rotation::identity(), true);
+    assert_eq!(m.conjugate_move("R".parse().unwrap()).to_string(), "L'");
+    assert_eq!(m.conjugate_move("U".parse().unwrap()).to_string(), "U'");
+}
+
+#[test]
+fn test_representative() {
+    let solved = State::new_solved();
+    let cb = &solved * "R U R' F2 D2 L";
+    let (rep, sym) = symmetry::representative(&cb);
+
+    assert_eq!(sym.conjugate(&cb), rep);
+
+    for s in Symmetry::all() {
+        assert_eq!(symmetry::representative(&s.conjugate(&cb)).0, rep);
+    }
+
+    assert_ne!(symmetry::representative(&(&solved * "R U")).0, rep);
+}
+
+#[test]
+fn test_stabilizer() {
+    let solved = State::new_solved();
+
+    assert_eq!(symmetry::stabilizer(&solved).len(), 48);
+    assert_eq!(symmetry::stabilizer(&(&solved * "U")).len(), 4);
+    assert_eq!(symmetry::stabilizer(&(&solved * "U2")).len(), 8);
+    assert_eq!(symmetry::stabilizer(&(&solved * "R U R' F2 D2 L")), vec![Symmetry::identity()]);
+}
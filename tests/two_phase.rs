@@ -0,0 +1,87 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use cube::algorithm::{Algorithm, Metric};
+use cube::scramble::Scrambler;
+use cube::solver::{two_phase, SolveError, SolveOptions};
+use cube::State;
+
+#[test]
+fn test_two_phase() {
+    let solved = State::new_solved();
+    let options = SolveOptions::two_phase();
+
+    assert_eq!(two_phase::solve(&solved, &options), Ok(vec![]));
+
+    for scramble in [
+        "U' F' D2 R U2 R' U2 F2 R D2 L2 D2 R' B U' L' B2 D2 B2 U2",
+        "R U R' F2 D2 L",
+        "D2 B2 L' F2 U2 R' D2 R' F2 R2 U2 B' L' D' F D' U2 R2 F' U",
+        "F R' B2 L U' D F2 R B' U2 L D' R2 F B' U L2 D B R'",
+    ] {
+        let cb = &solved * scramble;
+        let solution = two_phase::solve(&cb, &options).unwrap();
+
+        assert!(solution.len() <= options.max_length);
+        assert!(cb.apply_algorithm(&Algorithm::new(solution)).is_solved0());
+    }
+}
+
+#[test]
+fn test_two_phase_random_time() {
+    two_phase::init();
+
+    // 表を作った後は、どのランダムな状態もすぐに解ける
+    let mut scrambler = Scrambler::with_seed(123);
+    let options = SolveOptions::two_phase();
+
+    for _ in 0..200 {
+        let cb = scrambler.random_state();
+        let start = Instant::now();
+        let solution = two_phase::solve(&cb, &options).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+        assert!(solution.len() <= options.max_length);
+        assert!(cb.apply_algorithm(&Algorithm::new(solution)).is_solved0());
+    }
+}
+
+#[test]
+fn test_two_phase_rotated() {
+    // 持ち替えた状態はその向きのままそろえる
+    let cb = &State::new_solved() * "R U R' F2 D2 L x y'";
+    let solution = two_phase::solve(&cb, &SolveOptions::two_phase()).unwrap();
+
+    assert!(cb.apply_algorithm(&Algorithm::new(solution)).is_solved());
+}
+
+#[test]
+fn test_two_phase_errors() {
+    let st = State::product_of_cycles(&vec![vec![5, 19]]);
+    assert!(matches!(two_phase::solve(&st, &SolveOptions::two_phase()), Err(SolveError::Unsolvable(_))));
+
+    let cb = &State::new_solved() * "R U R' F2 D2 L";
+    let options = SolveOptions { max_length: 3, ..SolveOptions::two_phase() };
+    assert_eq!(two_phase::solve(&cb, &options), Err(SolveError::NotFound));
+
+    let options = SolveOptions { timeout: Some(Duration::ZERO), ..SolveOptions::two_phase() };
+    assert_eq!(two_phase::solve(&cb, &options), Err(SolveError::Timeout));
+
+    let options = SolveOptions { metric: Metric::Etm, ..SolveOptions::two_phase() };
+    assert_eq!(two_phase::solve(&cb, &options), Err(SolveError::UnsupportedMetric(Metric::Etm)));
+}
+
+#[test]
+fn test_two_phase_qtm() {
+    let solved = State::new_solved();
+
+    for scramble in ["R2 U2", "R U R' F2 D2 L", "F R' B2 L U' D F2 R B' U2"] {
+        let cb = &solved * scramble;
+        let max_length = Algorithm::from_str(scramble).unwrap().count(Metric::Qtm);
+        let options = SolveOptions { metric: Metric::Qtm, max_length, ..SolveOptions::two_phase() };
+        let solution = two_phase::solve(&cb, &options).unwrap();
+
+        assert!(Algorithm::new(solution.clone()).count(Metric::Qtm) <= max_length);
+        assert!(cb.apply_algorithm(&Algorithm::new(solution)).is_solved0());
+    }
+}